@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use fin_model::analysis::*;
 use fin_model::prelude::*;
-use fin_model::reporting::FinancialPeriod;
+use fin_model::reporting::{FinancialPeriod, FiscalCalendar};
 use fin_model::symbol::is_valid;
 
 use crate::internal::convert::*;
@@ -60,6 +60,8 @@ struct IEXEstimateData {
     fiscal_period: String,
     fiscal_end_date: String,
     report_date: String,
+    #[serde(default)]
+    updated: Option<f64>,
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -102,15 +104,16 @@ impl AnalystRecommendations for IEXProvider {
         match response {
             Ok(target) => {
                 record_api_use(ApiName::TargetPrice);
-                Ok(Snapshot {
-                    date: datetime_from_date_string(&target.updated_date)?,
-                    data: PriceTarget {
-                        high: price_from_float(dc, target.price_target_high)?,
-                        low: price_from_float(dc, target.price_target_low)?,
-                        average: price_from_float(dc, target.price_target_average)?,
+                Ok(Snapshot::new(
+                    datetime_from_date_string(&target.updated_date)?,
+                    PriceTarget {
+                        high: PerShare(price_from_float(dc, target.price_target_high)?),
+                        low: PerShare(price_from_float(dc, target.price_target_low)?),
+                        average: PerShare(price_from_float(dc, target.price_target_average)?),
                         number_of_analysts: target.number_of_analysts as u32,
                     },
-                })
+                )
+                .with_attribution(self.attribution_today()))
             }
             Err(err) => {
                 println!(
@@ -132,17 +135,18 @@ impl AnalystRecommendations for IEXProvider {
         let api_url =
             self.make_api_url(format!("/stock/{}/recommendation-trends", for_symbol), None);
 
+        // an unknown symbol is a 404, reported as not covered, while a covered
+        // symbol with no current ratings is an empty list.
         let response: RequestResult<Vec<IEXRecommendationTrends>> =
-            request::make_json_call(api_url);
+            request::make_symbol_json_call(api_url, &for_symbol);
         match response {
             Ok(consensus) => {
                 record_api_use(ApiName::ConsensusRatings);
-                let series: RequestResult<Vec<Bounded<Ratings>>> =
-                    consensus.iter().map(|v| to_rating(v)).collect();
-                match series {
-                    Ok(data) => Ok(data),
-                    Err(err) => Err(err),
-                }
+                let attribution = self.attribution_today();
+                consensus
+                    .iter()
+                    .map(|v| to_rating(v).map(|r| r.with_attribution(attribution.clone())))
+                    .collect()
             }
             Err(err) => {
                 warn!(
@@ -169,10 +173,11 @@ impl AnalystRecommendations for IEXProvider {
         match response {
             Ok(estimates) => {
                 record_api_use(ApiName::ConsensusEPS);
+                let attribution = self.attribution_today();
                 let series: RequestResult<Vec<EPSConsensus>> = estimates
                     .estimates
                     .iter()
-                    .map(|v| to_estimate(dc, v))
+                    .map(|v| to_estimate(dc, v).map(|e| e.with_attribution(attribution.clone())))
                     .collect();
                 match series {
                     Ok(data) => Ok(data),
@@ -202,22 +207,39 @@ fn to_rating(v: &IEXRecommendationTrends) -> RequestResult<Bounded<Ratings>> {
     ratings.insert(RatingType::Underperform, v.rating_underweight as Counter);
     ratings.insert(RatingType::Outperform, v.rating_overweight as Counter);
 
-    Ok(Bounded {
-        start_date: date_from_timestamp(v.consensus_start_date)?,
-        end_date: date_from_timestamp(v.consensus_end_date)?,
-        data: Ratings {
+    Ok(Bounded::new(
+        date_from_timestamp(v.consensus_start_date)?,
+        date_from_timestamp(v.consensus_end_date)?,
+        Ratings {
             ratings,
             scale_mark: Some(v.rating_scale_mark as f32),
         },
-    })
+    ))
 }
 
 fn to_estimate(dc: &String, v: &IEXEstimateData) -> RequestResult<EPSConsensus> {
+    // where the feed does not say when the estimate was updated it is first
+    // known today, see `EPSConsensus::published`.
+    let published = match v.updated {
+        Some(updated) => date_from_timestamp(updated)?.date(),
+        None => today_utc(),
+    };
+    // the feed reports the fiscal end date but not the calendar, so derive
+    // it; see `EPSConsensus::reconcile_fiscal_date` for an inconsistent date.
+    let fiscal_period = FinancialPeriod::from_str(v.fiscal_period.as_str()).unwrap();
+    let fiscal_end_date = date_from_string(&v.fiscal_end_date)?;
+    let fiscal_calendar =
+        FiscalCalendar::from_period_end(&fiscal_period, fiscal_end_date).unwrap_or_default();
     Ok(EPSConsensus {
-        consensus: price_from_float(dc, v.consensus_eps)?,
+        consensus: PerShare(price_from_float(dc, v.consensus_eps)?),
+        high_estimate: None,
+        low_estimate: None,
         number_of_estimates: v.number_of_estimates as Counter,
-        fiscal_period: FinancialPeriod::from_str(v.fiscal_period.as_str()).unwrap(),
-        fiscal_end_date: date_from_string(&v.fiscal_end_date)?,
+        fiscal_period,
+        fiscal_calendar,
+        fiscal_end_date,
         next_report_date: date_from_string(&v.report_date)?,
+        published,
+        attribution: None,
     })
 }
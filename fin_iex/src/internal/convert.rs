@@ -83,3 +83,24 @@ pub fn price_from_float(currency: &String, price: f64) -> RequestResult<Money> {
     // this isn't efficient, but deconstructing floats is a black art
     price_from_string(currency, &format!("{}", price))
 }
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_from_float_non_finite() {
+        let usd = "USD".to_string();
+        for price in &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(matches!(
+                price_from_float(&usd, *price),
+                Err(RequestError::BadResponseError)
+            ));
+        }
+        assert!(price_from_float(&usd, 12.0).is_ok());
+    }
+}
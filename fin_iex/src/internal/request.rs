@@ -30,13 +30,27 @@ pub fn make_api_call(api: String) -> RequestResult<String> {
 }
 
 pub fn make_json_call<T: DeserializeOwned>(api: String) -> RequestResult<T> {
+    json_call(api, None)
+}
+
+/// As `make_json_call`, but a `404 Not Found` response, where the service does
+/// not know `symbol`, is returned as `RequestError::BadSymbolError`.
+pub fn make_symbol_json_call<T: DeserializeOwned>(api: String, symbol: &str) -> RequestResult<T> {
+    json_call(api, Some(symbol))
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn json_call<T: DeserializeOwned>(api: String, symbol: Option<&str>) -> RequestResult<T> {
     info!("reqwest::get {}", api);
     let client = reqwest::Client::new();
     match client.get(api.as_str()).send() {
         Err(err) => match err.status() {
             Some(s) => {
                 warn!("response status {}", s);
-                Err(RequestError::from_u16(s.as_u16()).unwrap())
+                Err(status_error(s.as_u16(), symbol))
             }
             None => Err(RequestError::CommunicationError),
         },
@@ -51,8 +65,15 @@ pub fn make_json_call<T: DeserializeOwned>(api: String) -> RequestResult<T> {
                     Ok(j) => Ok(j),
                 }
             } else {
-                Err(RequestError::from_u16(r.status().as_u16()).unwrap())
+                Err(status_error(r.status().as_u16(), symbol))
             }
         }
     }
 }
+
+fn status_error(code: u16, symbol: Option<&str>) -> RequestError {
+    match (code, symbol) {
+        (404, Some(symbol)) => RequestError::BadSymbolError(symbol.to_string()),
+        _ => RequestError::from_u16(code).unwrap(),
+    }
+}
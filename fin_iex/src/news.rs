@@ -89,9 +89,9 @@ impl FetchNews for IEXProvider {
 }
 
 fn to_news_item(v: &IEXNewsItem) -> RequestResult<Snapshot<NewsItem>> {
-    Ok(Snapshot {
-        date: datetime_from_date_string(&v.datetime)?,
-        data: NewsItem {
+    Ok(Snapshot::new(
+        datetime_from_date_string(&v.datetime)?,
+        NewsItem {
             headline: v.headline.to_string(),
             source: v.source.to_string(),
             url: v.url.to_string(),
@@ -101,5 +101,5 @@ fn to_news_item(v: &IEXNewsItem) -> RequestResult<Snapshot<NewsItem>> {
             categories: None,
             sub_categories: None,
         },
-    })
+    ))
 }
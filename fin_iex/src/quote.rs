@@ -199,9 +199,9 @@ impl FetchPriceQuote for IEXProvider {
         match response {
             Ok(quote) => {
                 record_api_use(ApiName::Quote);
-                Ok(Quote {
-                    date: date_from_timestamp(quote.latest_update)?,
-                    data: QuotePriceFull {
+                Ok(Quote::new(
+                    date_from_timestamp(quote.latest_update)?,
+                    QuotePriceFull {
                         range: match (quote.open, quote.high, quote.low, quote.close) {
                             (Some(open), Some(high), Some(low), Some(close)) => Some(PriceRange {
                                 open: price_from_float(dc, open)?,
@@ -231,7 +231,7 @@ impl FetchPriceQuote for IEXProvider {
                             None => None,
                         },
                     },
-                })
+                ))
             }
             Err(err) => {
                 warn!(
@@ -257,9 +257,9 @@ impl FetchPriceQuote for IEXProvider {
         match response {
             Ok(quote) => {
                 record_api_use(ApiName::DelayedQuote);
-                Ok(DelayedQuote {
-                    date: date_from_timestamp(quote.delayed_price_time)?,
-                    data: QuotePriceDelayed {
+                Ok(DelayedQuote::new(
+                    date_from_timestamp(quote.delayed_price_time)?,
+                    QuotePriceDelayed {
                         latest: QuotePrice {
                             price: price_from_float(dc, quote.delayed_price)?,
                             change: None,
@@ -275,7 +275,7 @@ impl FetchPriceQuote for IEXProvider {
                         },
                         previous_close_date: None,
                     },
-                })
+                ))
             }
             Err(err) => {
                 warn!(
@@ -428,30 +428,30 @@ fn intraday_to_price_range(
     dc: &String,
     v: &IEXIntradayPrice,
 ) -> RequestResult<Snapshot<PriceRange>> {
-    Ok(Snapshot {
-        date: datetime_from_string(&v.date, &format!("{}:00", v.minute))?,
-        data: PriceRange {
+    Ok(Snapshot::new(
+        datetime_from_string(&v.date, &format!("{}:00", v.minute))?,
+        PriceRange {
             open: price_from_float(dc, v.high)?,
             close: price_from_float(dc, v.high)?,
             high: price_from_float(dc, v.high)?,
             low: price_from_float(dc, v.low)?,
             volume: Some(v.volume as u64),
         },
-    })
+    ))
 }
 
 fn historical_to_price_range(
     dc: &String,
     v: &IEXHistoricalPrice,
 ) -> RequestResult<Snapshot<PriceRange>> {
-    Ok(Snapshot {
-        date: datetime_from_date_string(&v.date)?,
-        data: PriceRange {
+    Ok(Snapshot::new(
+        datetime_from_date_string(&v.date)?,
+        PriceRange {
             open: price_from_float(dc, v.high)?,
             close: price_from_float(dc, v.high)?,
             high: price_from_float(dc, v.high)?,
             low: price_from_float(dc, v.low)?,
             volume: Some(v.volume as u64),
         },
-    })
+    ))
 }
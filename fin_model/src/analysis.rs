@@ -1,11 +1,39 @@
 /*!
 Provides structs and traits that represent common market analysis.
+
+## Negative Earnings
+
+Loss-making companies have a negative `EPSConsensus::consensus`, and ratios
+with earnings as the denominator are not meaningful for them. The valuation
+ratio functions therefore follow a single policy:
+
+* `forward_pe` returns `None` unless the consensus is positive, see
+  `EPSConsensus::is_profitable`, and so `peg_ratio`, `peg_from_consensus`, and
+  `peer_relative_zscore` also exclude loss-making companies.
+* `earnings_yield`, with earnings as the numerator, is defined for any
+  consensus and returns a negative yield for a loss.
 */
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fmt::Display;
+use std::iter::FromIterator;
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::Instant;
+
+use chrono::Datelike;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+use crate::company::FetchCompanyInformation;
+use crate::money::{money_from_f64, safe_div, try_money_from_f64};
 use crate::prelude::*;
-use crate::reporting::FinancialPeriod;
+use crate::provider::ProviderPolicy;
+use crate::reporting::{FinancialPeriod, FiscalCalendar};
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
@@ -15,7 +43,12 @@ use crate::reporting::FinancialPeriod;
 pub type Counter = u32;
 
 /// The type of an analyst recommendation/position.
-#[derive(PartialEq, Eq, Hash)]
+///
+/// Rating types implement both `fmt::Display` and `str::FromStr`, using the
+/// canonical lowercase names returned by `as_str`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum RatingType {
     /// Also known as strong buy and _on the recommended list_. Needless to say,
     /// buy is a recommendation to purchase a specific security.
@@ -36,7 +69,21 @@ pub enum RatingType {
     Sell,
 }
 
+/// The convention used by a provider to encode a `RatingType` as a number, see
+/// `RatingType::from_code` and `RatingType::to_code`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeScale {
+    /// codes `1..=5`, where `1` is buy and `5` is sell
+    OneIsBuy,
+    /// codes `1..=5`, where `1` is sell and `5` is buy
+    OneIsSell,
+    /// codes `0..=4`, where `0` is buy and `4` is sell
+    ZeroIsBuy,
+}
+
 /// The set of recommendation trends over some period of time.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Ratings {
     /// a mapping of available rating types to counts, not all types may be available
     pub ratings: HashMap<RatingType, Counter>,
@@ -45,70 +92,6257 @@ pub struct Ratings {
 }
 
 /// Consensus price targets; high, low, and average.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PriceTarget {
     /// anticipated high price
-    pub high: Money,
+    pub high: PerShare,
     /// anticipated low price
-    pub low: Money,
+    pub low: PerShare,
     /// anticipated average price
-    pub average: Money,
+    pub average: PerShare,
     /// number of analysts that provided recommendations
     pub number_of_analysts: Counter,
 }
 
 /// Consensus Earnings per Share (EPS) targets for some fiscal period.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EPSConsensus {
     /// anticipated earnings per share
-    pub consensus: Money,
+    pub consensus: PerShare,
+    /// the highest individual estimate, if known
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub high_estimate: Option<PerShare>,
+    /// the lowest individual estimate, if known
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub low_estimate: Option<PerShare>,
     /// number of analysts that provided recommendations
     pub number_of_estimates: Counter,
     /// expected for this period
     pub fiscal_period: FinancialPeriod,
+    /// the fiscal calendar that `fiscal_period` is relative to
+    pub fiscal_calendar: FiscalCalendar,
     /// the company's end date for `fiscal_period`
     pub fiscal_end_date: Date,
     /// anticipated next reporting date
     pub next_report_date: Date,
+    /// the date on which this consensus was published, or first known
+    pub published: Date,
+    /// the provider that produced the consensus, if known; see `with_attribution`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attribution: Option<Attribution>,
+}
+
+/// The overall direction of a set of ratings, see `Ratings::classify`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sentiment {
+    /// the average rating is below the bullish threshold, towards buy
+    Bullish,
+    /// the average rating is between the bullish and bearish thresholds
+    Neutral,
+    /// the average rating is above the bearish threshold, towards sell
+    Bearish,
+}
+
+/// A single, headline, analyst verdict for a symbol, see `verdict`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    /// bullish ratings with a large upside, or a large upside alone
+    StrongBuy,
+    /// a positive outlook that falls short of a strong buy
+    Buy,
+    /// no clear signal either way, or ratings and upside that disagree
+    Hold,
+    /// a negative outlook that falls short of a strong sell
+    Sell,
+    /// bearish ratings with a large downside, or a large downside alone
+    StrongSell,
+}
+
+/// The thresholds, on the `Ratings::scaled_average` scale of 1.0 (buy) to 5.0
+/// (sell), used to classify a set of ratings as bullish or bearish. The default
+/// places the boundaries at the midpoints either side of hold, so an average of
+/// `2.5..=3.5` is neutral.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsensusThresholds {
+    /// averages strictly below this value are bullish
+    pub bullish_below: f64,
+    /// averages strictly above this value are bearish
+    pub bearish_above: f64,
+}
+
+/// An individual analyst's estimates for a symbol, as opposed to the consensus
+/// values above. Not all analysts provide all values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalystEstimate {
+    /// the firm the analyst works for
+    pub firm: String,
+    /// the (optional) price target
+    pub target: Option<PerShare>,
+    /// the (optional) recommendation
+    pub rating: Option<RatingType>,
+    /// the (optional) earnings per share estimate
+    pub eps: Option<PerShare>,
+    /// the date of the estimate
+    pub date: Date,
+}
+
+/// A change in an individual firm's rating for a symbol; `from` is `None` when
+/// the firm initiates coverage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RatingEvent {
+    /// the firm changing its rating
+    pub firm: String,
+    /// the (optional) previous rating
+    pub from: Option<RatingType>,
+    /// the new rating
+    pub to: RatingType,
+    /// the date of the change
+    pub date: Date,
+}
+
+/// The relative weights of each component of `composite_score`; weights need
+/// not sum to `1.0` as they are normalized over the components available.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreWeights {
+    /// weight of the latest consensus rating
+    pub rating: f64,
+    /// weight of the upside implied by the price target
+    pub target_upside: f64,
+    /// weight of the growth in consensus EPS
+    pub eps_growth: f64,
+}
+
+/// Selects one of the values of a `PriceTarget`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TargetBound {
+    /// the `high` target
+    High,
+    /// the `low` target
+    Low,
+    /// the `average` target
+    Average,
+}
+
+/// An estimated dividend payment for a fiscal period.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DividendEstimate {
+    /// the estimated amount per share
+    pub amount: PerShare,
+    /// the fiscal period the dividend is paid for
+    pub fiscal_period: FinancialPeriod,
+    /// the (expected) ex-dividend date
+    pub ex_date: Date,
+}
+
+/// The kind of an upcoming event that may move a stock, see `next_catalyst`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CatalystKind {
+    /// an earnings report, from `EPSConsensus::next_report_date`
+    Earnings,
+    /// an ex-dividend date, from `DividendEstimate::ex_date`
+    ExDividend,
+}
+
+/// A stock split, or reverse split, that takes effect on `date`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Split {
+    /// the date on which the split takes effect
+    pub date: Date,
+    /// the number of new shares for each existing share, `2.0` for a 2-for-1
+    /// split and `0.1` for a 1-for-10 reverse split
+    pub ratio: f64,
+}
+
+/// The direction of a change in consensus rating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// the consensus moved towards buy
+    Upgrade,
+    /// the consensus moved towards sell
+    Downgrade,
+}
+
+/// A change in the consensus rating between consecutive ratings periods, see
+/// `detect_rating_changes`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RatingChangeEvent {
+    /// the start date of the later period
+    pub date: Date,
+    /// the direction of the change
+    pub direction: Direction,
+    /// the absolute change in `Ratings::scaled_average`
+    pub magnitude: f64,
+}
+
+/// The default `RatingScorer`, scoring ratings by `Ratings::outperform_probability`
+/// so that each step from sell to buy adds the same amount to the score.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LinearScorer;
+
+/// A `RatingScorer` that gives more weight to the more positive ratings. A rating
+/// `k` steps from sell, of a maximum of 4, scores `(base^k - 1) / (base^4 - 1)`
+/// and the score is the count-weighted mean of these values; so with a `base`
+/// of `2.0` a hold scores `0.2` rather than `0.5`. A `base` of `1.0` or less
+/// scores as `LinearScorer`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExponentialScorer {
+    /// the base of the exponential scale
+    pub base: f64,
+}
+
+/// The complete set of analyst data for a single symbol, combining the results
+/// of the `AnalystRecommendations` methods.
+///
+/// With the `serde` feature the data can be written to, and read from, a JSON
+/// document with a `schema_version`, see `to_json` and `from_json`.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AnalystData {
+    /// the (optional) current price target
+    pub target: Option<Snapshot<PriceTarget>>,
+    /// the consensus ratings over time
+    pub ratings: Vec<Bounded<Ratings>>,
+    /// the consensus EPS for a number of fiscal periods
+    pub eps: Vec<EPSConsensus>,
+}
+
+/// Assembles `AnalystData` incrementally, such as from a number of providers,
+/// see `AnalystData::builder`.
+#[derive(Debug, Clone, Default)]
+pub struct AnalystDataBuilder {
+    data: AnalystData,
+}
+
+/// A ratings history stored as parallel columns, one value per period in each,
+/// rather than as a list of `Bounded<Ratings>`; see `ratings_history_to_columns`.
+/// A count is `None` where the rating type was absent from the period.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct RatingsColumns {
+    /// the start date of each period
+    pub start_dates: Vec<DateTime>,
+    /// the end date of each period
+    pub end_dates: Vec<DateTime>,
+    /// the (optional) attribution of the provider of each period
+    pub attributions: Vec<Option<Attribution>>,
+    /// the (optional) scale mark of each period
+    pub scale_marks: Vec<Option<f32>>,
+    /// the count of buy ratings in each period
+    pub buy: Vec<Option<Counter>>,
+    /// the count of outperform ratings in each period
+    pub outperform: Vec<Option<Counter>>,
+    /// the count of hold ratings in each period
+    pub hold: Vec<Option<Counter>>,
+    /// the count of underperform ratings in each period
+    pub underperform: Vec<Option<Counter>>,
+    /// the count of sell ratings in each period
+    pub sell: Vec<Option<Counter>>,
+}
+
+/// A single dated item of analyst data, used to build a chronological timeline
+/// of analyst activity with `build_timeline`.
+#[derive(Debug, Clone)]
+pub enum TimelineEvent {
+    /// a price target, dated by the snapshot date
+    Target(Snapshot<PriceTarget>),
+    /// a ratings period, dated by the start of the period
+    Rating(Bounded<Ratings>),
+    /// an EPS consensus, dated by the fiscal period end date
+    Eps(EPSConsensus),
+}
+
+/// The differences between the analyst data returned by two providers for the
+/// same symbol, see `reconcile`. Each delta is calculated as the first provider's
+/// value minus the second's, and is `None` if either provider has no such data.
+#[derive(Debug, PartialEq)]
+pub struct ReconciliationReport {
+    /// the difference in average price target
+    pub target_delta: Option<PerShare>,
+    /// the difference in the `scaled_average` of the latest consensus ratings
+    pub rating_delta: Option<f64>,
+    /// the difference in consensus EPS for the nearest fiscal period
+    pub eps_delta: Option<PerShare>,
+}
+
+/// The change in a consensus price target between two snapshots, see `diff_targets`.
+#[derive(Debug, PartialEq)]
+pub enum TargetChange {
+    /// the average target increased, by the included amount
+    Raised(PerShare),
+    /// the average target decreased, by the included (negative) amount
+    Lowered(PerShare),
+    /// the average target did not move by more than the threshold
+    Unchanged,
+    /// there was no previous target, but there is now
+    Initiated,
+    /// there was a previous target, but there is not now
+    Dropped,
+    /// the targets, or the threshold, are in different currencies, or the
+    /// change is too large to represent, so the targets cannot be compared
+    Incomparable,
+}
+
+/// An iterator over the pages of peer symbols returned by a `PagedPeers`
+/// provider, see `PagedPeers::pages`. Iteration stops after the last page
+/// or after the first error is returned.
+pub struct PeerPages<'a, P: PagedPeers + ?Sized> {
+    provider: &'a P,
+    for_symbol: Symbol,
+    cursor: Option<String>,
+    done: bool,
+}
+
+/// Combines two `AnalystRecommendations` providers, where each request is made
+/// of the `primary` provider and, if it fails or returns no data, of the
+/// `secondary`. This allows one provider's data to be enriched with another's,
+/// method by method. Where the providers are named, see `with_sources`, price
+/// targets, ratings, and EPS consensus values without a source are attributed
+/// to the provider that returned them.
+pub struct MergingRecommendations<A, B> {
+    primary: A,
+    secondary: B,
+    primary_source: Option<String>,
+    secondary_source: Option<String>,
+}
+
+/// Wraps an `AnalystRecommendations` provider, applying the caching, retry, and
+/// rate-limiting of a `ProviderPolicy`. Each request is first answered from the
+/// cache, if a result for the same request is younger than `ttl`; otherwise it
+/// is made of the provider, waiting so that requests, including retries, are at
+/// least `min_interval` apart. A request that fails with a transient error,
+/// `CommunicationError` or `RequestThrottled`, is retried up to `max_attempts`
+/// in total, with exponential backoff. Only successful results are cached.
+pub struct Wrapped<P> {
+    provider: P,
+    policy: ProviderPolicy,
+    state: Mutex<WrappedState>,
+}
+
+/// An append-only, in-memory store of consensus history by symbol. EPS
+/// consensus values are kept ordered by `published` date, ratings by
+/// `start_date`, and targets by `date`; identical records are only stored once.
+#[derive(Debug, Clone, Default)]
+pub struct ConsensusStore {
+    eps: HashMap<Symbol, Vec<EPSConsensus>>,
+    ratings: HashMap<Symbol, Vec<Bounded<Ratings>>>,
+    targets: HashMap<Symbol, Vec<Snapshot<PriceTarget>>>,
+}
+
+/// The change in EPS consensus for a fiscal period, see
+/// `ConsensusStore::record_and_diff`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpsChange {
+    /// the fiscal period of both estimates
+    pub fiscal_period: FinancialPeriod,
+    /// the prior consensus
+    pub previous: PerShare,
+    /// the newly recorded consensus
+    pub current: PerShare,
+    /// `current - previous`, or `None` if they are in different currencies
+    pub delta: Option<PerShare>,
+}
+
+/// Tracks the accuracy of price targets, such as those from a single provider,
+/// against the prices realized at their horizon dates. Targets are held until
+/// `evaluate` is given a realized price for their horizon, after which they
+/// contribute to every later report.
+#[derive(Debug, Clone, Default)]
+pub struct AccuracyTracker {
+    pending: Vec<(PriceTarget, Date)>,
+    total_error: f64,
+    hits: usize,
+    count: usize,
+}
+
+/// The accuracy of all targets evaluated by an `AccuracyTracker`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AccuracyReport {
+    /// the mean absolute error of the target average, relative to the realized price
+    pub mae: f64,
+    /// the fraction of targets where the realized price was within the range from low to high
+    pub hit_rate: f64,
+    /// the number of targets evaluated
+    pub count: usize,
+}
+
+/// The analyst coverage of a universe of symbols, see `coverage_stats`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CoverageStats {
+    /// the number of symbols with a price target
+    pub with_targets: usize,
+    /// the number of symbols with consensus ratings
+    pub with_ratings: usize,
+    /// the number of symbols with consensus EPS
+    pub with_eps: usize,
+    /// the number of symbols with none of the above
+    pub uncovered: usize,
+    /// the mean number of analysts providing the price targets
+    pub avg_analysts: f64,
+}
+
+/// A linear mapping from one provider's rating scale onto another's, see
+/// `calibrate_scales`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleMapping {
+    /// the change in the target scale for each unit of the source scale
+    pub slope: f64,
+    /// the value on the target scale of zero on the source scale
+    pub intercept: f64,
+}
+
+/// The coverage of a symbol by a provider, see `coverage_status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoverageStatus {
+    /// the provider does not cover the symbol
+    NotCovered,
+    /// the provider covers the symbol, but has no data for it
+    CoveredNoData,
+    /// the provider covers the symbol, and has data for it
+    CoveredWithData,
+}
+
+/// Errors that can result from validating analysis values, such as `PriceTarget::validate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationError {
+    /// the monetary values are not all in the same currency
+    CurrencyMismatch,
+    /// a monetary value is negative
+    NegativeValue,
+    /// the low value is greater than the high value
+    LowAboveHigh,
+    /// the average value lies outside the range from low to high
+    AverageOutOfRange,
+    /// the EPS consensus lies outside the range of individual estimates
+    ConsensusOutOfRange,
+    /// the fiscal period is not valid, see `FinancialPeriod::is_valid`
+    InvalidFiscalPeriod,
+    /// the number of estimates is zero
+    NoEstimates,
+    /// the next report date is before the end of the fiscal period
+    ReportBeforeFiscalEnd,
+}
+
+/// A summary of the validation of a collection of values, see `validate_batch`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ValidationSummary {
+    /// the number of values validated
+    pub total: usize,
+    /// the number of values that passed validation
+    pub valid: usize,
+    /// the number of values that failed with each error
+    pub by_error: HashMap<ValidationError, usize>,
+}
+
+/// Errors that can result from analysis functions that combine values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnalysisError {
+    /// the values combined use different fiscal calendars, so their fiscal
+    /// periods are not comparable
+    MixedFiscalCalendars,
+    /// the values combined are not all in the same currency
+    MixedCurrencies,
+}
+
+/// Errors that can result from parsing a `RatingType` from a string.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// the string is empty
+    EmptyString,
+    /// the string is not one of the known rating names
+    InvalidRatingString,
 }
 
 // ------------------------------------------------------------------------------------------------
 // Public Traits
 // ------------------------------------------------------------------------------------------------
 
+impl RatingType {
+    /// Return the canonical lowercase name for this rating type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RatingType::Buy => "buy",
+            RatingType::Outperform => "outperform",
+            RatingType::Hold => "hold",
+            RatingType::Underperform => "underperform",
+            RatingType::Sell => "sell",
+        }
+    }
+
+    /// Decode a numeric rating `code` using the convention `scale`, returning
+    /// `None` if the code is out of range for the scale.
+    pub fn from_code(code: u8, scale: CodeScale) -> Option<RatingType> {
+        let ordinal = match scale {
+            CodeScale::OneIsBuy => code.checked_sub(1)?,
+            CodeScale::OneIsSell => 5u8.checked_sub(code)?,
+            CodeScale::ZeroIsBuy => code,
+        };
+        match ordinal {
+            0 => Some(RatingType::Buy),
+            1 => Some(RatingType::Outperform),
+            2 => Some(RatingType::Hold),
+            3 => Some(RatingType::Underperform),
+            4 => Some(RatingType::Sell),
+            _ => None,
+        }
+    }
+
+    /// Encode this rating as a number using the convention `scale`.
+    pub fn to_code(&self, scale: CodeScale) -> u8 {
+        let ordinal = match self {
+            RatingType::Buy => 0,
+            RatingType::Outperform => 1,
+            RatingType::Hold => 2,
+            RatingType::Underperform => 3,
+            RatingType::Sell => 4,
+        };
+        match scale {
+            CodeScale::OneIsBuy => ordinal + 1,
+            CodeScale::OneIsSell => 5 - ordinal,
+            CodeScale::ZeroIsBuy => ordinal,
+        }
+    }
+}
+
+impl Display for RatingType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for RatingType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseError::EmptyString);
+        }
+        match s.to_lowercase().as_str() {
+            "buy" => Ok(RatingType::Buy),
+            "outperform" => Ok(RatingType::Outperform),
+            "hold" => Ok(RatingType::Hold),
+            "underperform" => Ok(RatingType::Underperform),
+            "sell" => Ok(RatingType::Sell),
+            _ => Err(ParseError::InvalidRatingString),
+        }
+    }
+}
+
 impl Ratings {
     /// Calculate the scaled/weighted average of the current set of ratings.
+    ///
+    /// Counts are accumulated as `u64` values so that even counts close to
+    /// `Counter::MAX` cannot overflow.
     pub fn scaled_average(&self) -> f64 {
-        let (count, total) = self.ratings.iter().fold((0, 0), |(c, t), (k, v)| {
-            (
-                c + *v,
-                t + match *k {
-                    RatingType::Buy => 1,
-                    RatingType::Outperform => 2,
-                    RatingType::Hold => 3,
-                    RatingType::Underperform => 4,
-                    RatingType::Sell => 5,
-                } * *v,
+        let (count, total) = self.ratings.iter().fold((0u64, 0u64), |(c, t), (k, v)| {
+            let v = u64::from(*v);
+            (c + v, t + u64::from(k.to_code(CodeScale::OneIsBuy)) * v)
+        });
+        safe_div(total as f64, count as f64).unwrap_or(f64::NAN)
+    }
+
+    /// Returns `true` if there are no ratings counted in this set.
+    pub fn is_empty(&self) -> bool {
+        self.ratings.values().all(|count| *count == 0)
+    }
+
+    /// Return a stable fingerprint of the ratings; equal ratings always have
+    /// the same fingerprint, across process runs and platforms. The counts
+    /// are included in a canonical order of rating type.
+    pub fn fingerprint(&self) -> u64 {
+        let mut counts: Vec<(u8, Counter)> = self
+            .ratings
+            .iter()
+            .map(|(rating, count)| (rating.to_code(CodeScale::OneIsBuy), *count))
+            .collect();
+        counts.sort_unstable();
+        let mut fp = Fingerprint::new();
+        for (code, count) in counts {
+            fp.write(&[code]);
+            fp.write(&count.to_le_bytes());
+        }
+        match self.scale_mark {
+            None => fp.write(&[0]),
+            Some(mark) => {
+                fp.write(&[1]);
+                fp.write(&mark.to_bits().to_le_bytes());
+            }
+        }
+        fp.finish()
+    }
+
+    /// Express the ratings as a probability of outperformance, in the range
+    /// `0.0..=1.0`, or `None` if there are no ratings. Each rating counts as
+    /// evidence; buy as `1.0`, outperform as `0.75`, hold as the neutral `0.5`,
+    /// underperform as `0.25`, and sell as `0.0`, and the result is the
+    /// count-weighted mean of these values. So an all-hold consensus is `0.5`.
+    pub fn outperform_probability(&self) -> Option<f64> {
+        if self.is_empty() {
+            None
+        } else {
+            Some((5.0 - self.scaled_average()) / 4.0)
+        }
+    }
+
+    /// Express the ratings as a net sentiment, in the range `-1.0..=1.0`, the
+    /// number of bullish (buy and outperform) ratings less the number of
+    /// bearish (underperform and sell) ratings, as a fraction of all ratings.
+    /// Hold ratings count only towards the total, so an all-hold consensus is
+    /// `0.0`. Returns `None` if there are no ratings.
+    pub fn net_sentiment(&self) -> Option<f64> {
+        let (net, total) = self.ratings.iter().fold((0i64, 0u64), |(n, t), (k, v)| {
+            let sign = match k {
+                RatingType::Buy | RatingType::Outperform => 1,
+                RatingType::Hold => 0,
+                RatingType::Underperform | RatingType::Sell => -1,
+            };
+            (n + sign * i64::from(*v), t + u64::from(*v))
+        });
+        safe_div(net as f64, total as f64)
+    }
+
+    /// Merge two sets of ratings, such as those from different providers, by
+    /// adding the counts for each rating type. The merged ratings retain the
+    /// `scale_mark` only if both inputs have one and they are equal (within
+    /// a small tolerance), otherwise it is `None`.
+    pub fn merge(&self, other: &Ratings) -> Ratings {
+        let mut ratings = self.ratings.clone();
+        for (rating, count) in &other.ratings {
+            let total = ratings.entry(*rating).or_insert(0);
+            *total = total.saturating_add(*count);
+        }
+        let scale_mark = match (self.scale_mark, other.scale_mark) {
+            (Some(a), Some(b)) if (a - b).abs() <= SCALE_MARK_TOLERANCE => Some(a),
+            _ => None,
+        };
+        Ratings {
+            ratings,
+            scale_mark,
+        }
+    }
+
+    /// Return the rating type nearest to the `scaled_average`, the consensus
+    /// rating, or `None` if there are no ratings. An average exactly between
+    /// two types rounds toward sell, so `2.5` is `Hold`.
+    pub fn consensus_type(&self) -> Option<RatingType> {
+        if self.is_empty() {
+            None
+        } else {
+            RatingType::from_code(self.scaled_average().round() as u8, CodeScale::OneIsBuy)
+        }
+    }
+
+    /// Replace the `scale_mark` with the `scaled_average` of the current set
+    /// of ratings, or `None` if there are no ratings.
+    pub fn recompute_scale_mark(&mut self) {
+        self.scale_mark = if self.is_empty() {
+            None
+        } else {
+            Some(self.scaled_average() as f32)
+        };
+    }
+
+    /// Classify the `scaled_average` of these ratings using the thresholds `t`,
+    /// returning `None` if there are no ratings.
+    pub fn classify(&self, t: &ConsensusThresholds) -> Option<Sentiment> {
+        if self.is_empty() {
+            return None;
+        }
+        let average = self.scaled_average();
+        Some(if average < t.bullish_below {
+            Sentiment::Bullish
+        } else if average > t.bearish_above {
+            Sentiment::Bearish
+        } else {
+            Sentiment::Neutral
+        })
+    }
+}
+
+/// Count each observed rating, the collected ratings have no `scale_mark`; use
+/// `Ratings::recompute_scale_mark` if one is required.
+impl FromIterator<RatingType> for Ratings {
+    fn from_iter<I: IntoIterator<Item = RatingType>>(iter: I) -> Self {
+        let mut ratings = Ratings {
+            ratings: HashMap::new(),
+            scale_mark: None,
+        };
+        ratings.extend(iter);
+        ratings
+    }
+}
+
+/// Add each observed rating to the existing counts, this leaves any existing
+/// `scale_mark` unchanged.
+impl Extend<RatingType> for Ratings {
+    fn extend<I: IntoIterator<Item = RatingType>>(&mut self, iter: I) {
+        for rating in iter {
+            *self.ratings.entry(rating).or_insert(0) += 1;
+        }
+    }
+}
+
+impl EPSConsensus {
+    /// Construct a new consensus, returning it only if it passes `validate`.
+    /// The range of individual estimates is not known, see `with_estimate_range`.
+    pub fn try_new(
+        consensus: PerShare,
+        number_of_estimates: Counter,
+        fiscal_period: FinancialPeriod,
+        fiscal_calendar: FiscalCalendar,
+        fiscal_end_date: Date,
+        next_report_date: Date,
+        published: Date,
+    ) -> Result<EPSConsensus, ValidationError> {
+        let eps = EPSConsensus {
+            consensus,
+            high_estimate: None,
+            low_estimate: None,
+            number_of_estimates,
+            fiscal_period,
+            fiscal_calendar,
+            fiscal_end_date,
+            next_report_date,
+            published,
+            attribution: None,
+        };
+        eps.validate()?;
+        Ok(eps)
+    }
+
+    /// Set the highest and lowest individual estimates.
+    pub fn with_estimate_range(self, high: PerShare, low: PerShare) -> Self {
+        EPSConsensus {
+            high_estimate: Some(high),
+            low_estimate: Some(low),
+            ..self
+        }
+    }
+
+    /// Set the name of the provider that produced this consensus; the
+    /// attribution is as of the `published` date, use `with_attribution` to set
+    /// another.
+    pub fn with_source(self, source: impl Into<String>) -> Self {
+        let as_of = self.published;
+        self.with_attribution(Attribution::new(source, as_of))
+    }
+
+    /// Set the attribution of the provider that produced this consensus.
+    pub fn with_attribution(self, attribution: Attribution) -> Self {
+        EPSConsensus {
+            attribution: Some(attribution),
+            ..self
+        }
+    }
+
+    /// Return the name of the provider that produced this consensus, if known.
+    pub fn source(&self) -> Option<&str> {
+        self.attribution.as_ref().map(|a| a.provider.as_str())
+    }
+
+    /// Validate the consensus to ensure that the fiscal period is valid, there
+    /// is at least one estimate, and that the next report date is not before
+    /// the fiscal period end date. Where the estimate range, or either end of
+    /// it, is known it must be in the currency of the consensus, with
+    /// `low_estimate <= consensus <= high_estimate`.
+    ///
+    /// There is no check for non-finite values, as `Money` holds whole minor
+    /// units in an `i32` and so cannot be NaN or infinite; such values are
+    /// rejected where a provider's floating point value is converted, by
+    /// `money::try_money_from_f64` or, for IEX, `price_from_float`.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if !self.fiscal_period.is_valid() {
+            return Err(ValidationError::InvalidFiscalPeriod);
+        }
+        if self.number_of_estimates == 0 {
+            return Err(ValidationError::NoEstimates);
+        }
+        if self.next_report_date < self.fiscal_end_date {
+            return Err(ValidationError::ReportBeforeFiscalEnd);
+        }
+        match self.estimate_range_errors().first() {
+            None => Ok(()),
+            Some(err) => Err(*err),
+        }
+    }
+
+    /// Perform the same checks as `validate`, but return every failure rather
+    /// than only the first.
+    pub fn validate_all(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        if !self.fiscal_period.is_valid() {
+            errors.push(ValidationError::InvalidFiscalPeriod);
+        }
+        if self.number_of_estimates == 0 {
+            errors.push(ValidationError::NoEstimates);
+        }
+        if self.next_report_date < self.fiscal_end_date {
+            errors.push(ValidationError::ReportBeforeFiscalEnd);
+        }
+        errors.extend(self.estimate_range_errors());
+        as_validation_result(errors)
+    }
+
+    /// Return a stable fingerprint of all the fields of the consensus, other
+    /// than the `attribution` of its source; equal values always have the same
+    /// fingerprint, across process runs and platforms.
+    pub fn fingerprint(&self) -> u64 {
+        let mut fp = Fingerprint::new();
+        fp.write_money(self.consensus.0);
+        fp.write_option_money(self.high_estimate.map(|high| high.0));
+        fp.write_option_money(self.low_estimate.map(|low| low.0));
+        fp.write(&self.number_of_estimates.to_le_bytes());
+        fp.write_str(&self.fiscal_period.to_string());
+        fp.write(&self.fiscal_calendar.start_month.to_le_bytes());
+        fp.write_date(self.fiscal_end_date);
+        fp.write_date(self.next_report_date);
+        fp.write_date(self.published);
+        fp.finish()
+    }
+
+    /// Correct the `fiscal_end_date` if it does not fall within the fiscal period
+    /// in `calendar`, replacing it with the end date of the period, see
+    /// `FinancialPeriod::end_date_in`. The consensus is taken to be reported in
+    /// `calendar`, so `fiscal_calendar` is set to it.
+    ///
+    /// Rather than a flag, this returns the original date if it was corrected,
+    /// so that the correction can be audited; `is_some()` is the flag. `None` is
+    /// returned if the date was already consistent, or if the fiscal period is
+    /// not valid and so no date can be consistent with it.
+    pub fn reconcile_fiscal_date(&mut self, calendar: &FiscalCalendar) -> Option<Date> {
+        self.fiscal_calendar = *calendar;
+        if !self.fiscal_period.is_valid()
+            || self
+                .fiscal_period
+                .contains_in(self.fiscal_end_date, calendar)
+        {
+            None
+        } else {
+            let original = self.fiscal_end_date;
+            self.fiscal_end_date = self.fiscal_period.end_date_in(calendar);
+            Some(original)
+        }
+    }
+
+    /// Returns `true` if the consensus is for positive earnings.
+    pub fn is_profitable(&self) -> bool {
+        self.consensus.0.minor_amount() > 0
+    }
+
+    /// Returns `true` if `today` is after the `next_report_date`, in which case
+    /// the company has likely already reported and the estimate is stale.
+    pub fn is_stale(&self, today: Date) -> bool {
+        today > self.next_report_date
+    }
+
+    /// Normalize the consensus to the currency `to`, using the exchange rate
+    /// on `fiscal_end_date`, and to a per-underlying-share basis by dividing
+    /// by `adr_ratio`, the number of underlying shares per depositary receipt.
+    /// An `adr_ratio` of `1.0` in the same currency returns an equal value;
+    /// a non-positive `adr_ratio` returns `RequestError::BadRequestError`.
+    pub fn normalize(
+        &self,
+        to: Currency,
+        adr_ratio: f64,
+        rates: &impl ExchangeRates,
+    ) -> RequestResult<EPSConsensus> {
+        if adr_ratio.is_nan() || adr_ratio <= 0.0 {
+            return Err(RequestError::BadRequestError);
+        }
+        let normalize = |value: PerShare| -> RequestResult<PerShare> {
+            Ok(PerShare(
+                value.0.convert(to, rates, self.fiscal_end_date)? * adr_ratio.recip(),
+            ))
+        };
+        Ok(EPSConsensus {
+            consensus: normalize(self.consensus)?,
+            high_estimate: self.high_estimate.map(normalize).transpose()?,
+            low_estimate: self.low_estimate.map(normalize).transpose()?,
+            ..self.clone()
+        })
+    }
+
+    /// Approximate the coefficient of variation of the individual estimates,
+    /// a measure of analyst disagreement relative to the size of the number.
+    /// The standard deviation is estimated as a quarter of the range from the
+    /// lowest to the highest estimate, so this is
+    /// `(high_estimate - low_estimate) / (4 * |consensus|)`.
+    ///
+    /// Returns `None` if the range is not known, the values are in different
+    /// currencies, or the consensus is zero.
+    pub fn estimate_cv(&self) -> Option<f64> {
+        let (high, low) = (self.high_estimate?.0, self.low_estimate?.0);
+        let currency = self.consensus.0.currency;
+        if high.currency != currency || low.currency != currency {
+            return None;
+        }
+        safe_div(
+            high.as_f64() - low.as_f64(),
+            4.0 * self.consensus.0.as_f64().abs(),
+        )
+    }
+
+    fn estimate_range_errors(&self) -> Vec<ValidationError> {
+        let currency = self.consensus.0.currency;
+        let (high, low) = (self.high_estimate, self.low_estimate);
+        if [high, low]
+            .iter()
+            .flatten()
+            .any(|v| v.0.currency != currency)
+        {
+            return vec![ValidationError::CurrencyMismatch];
+        }
+        let mut errors = Vec::new();
+        if let (Some(high), Some(low)) = (high, low) {
+            if low > high {
+                errors.push(ValidationError::LowAboveHigh);
+            }
+        }
+        if high.is_some_and(|high| self.consensus > high)
+            || low.is_some_and(|low| self.consensus < low)
+        {
+            errors.push(ValidationError::ConsensusOutOfRange);
+        }
+        errors
+    }
+}
+
+impl Default for ConsensusThresholds {
+    fn default() -> Self {
+        ConsensusThresholds {
+            bullish_below: 2.5,
+            bearish_above: 3.5,
+        }
+    }
+}
+
+impl TimelineEvent {
+    /// Return the date used to order this event in a timeline.
+    pub fn date(&self) -> Date {
+        match self {
+            TimelineEvent::Target(snapshot) => snapshot.date.date(),
+            TimelineEvent::Rating(bounded) => bounded.start_date.date(),
+            TimelineEvent::Eps(eps) => eps.fiscal_end_date,
+        }
+    }
+}
+
+impl PriceTarget {
+    /// Construct a new target, returning it only if it passes `validate`.
+    pub fn try_new(
+        high: PerShare,
+        low: PerShare,
+        average: PerShare,
+        number_of_analysts: Counter,
+    ) -> Result<PriceTarget, ValidationError> {
+        let target = PriceTarget {
+            high,
+            low,
+            average,
+            number_of_analysts,
+        };
+        target.validate()?;
+        Ok(target)
+    }
+
+    /// Construct a target from only the `high` and `low` prices, for providers
+    /// that do not supply an average. The `average` is an approximation, the
+    /// midpoint of the range rounded down to a minor unit, not the mean of the
+    /// individual targets. The result passes `validate` if `high` and `low`
+    /// are non-negative, in the same currency, and `low <= high`.
+    pub fn infer_average(
+        high: PerShare,
+        low: PerShare,
+        number_of_analysts: Counter,
+    ) -> PriceTarget {
+        let midpoint = (i64::from(high.0.minor_amount()) + i64::from(low.0.minor_amount())) / 2;
+        PriceTarget {
+            high,
+            low,
+            average: PerShare(Money::of_minor(high.0.currency, midpoint as i32)),
+            number_of_analysts,
+        }
+    }
+
+    /// Validate the target to ensure that all prices are non-negative, in the
+    /// same currency, and that `low <= average <= high`. As for
+    /// `EPSConsensus::validate`, prices cannot be NaN or infinite and are not
+    /// checked for it.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let currency = self.average.0.currency;
+        if self.high.0.currency != currency || self.low.0.currency != currency {
+            return Err(ValidationError::CurrencyMismatch);
+        }
+        if self.high.0.minor_amount() < 0
+            || self.low.0.minor_amount() < 0
+            || self.average.0.minor_amount() < 0
+        {
+            return Err(ValidationError::NegativeValue);
+        }
+        if self.low > self.high {
+            return Err(ValidationError::LowAboveHigh);
+        }
+        if self.average < self.low || self.average > self.high {
+            return Err(ValidationError::AverageOutOfRange);
+        }
+        Ok(())
+    }
+
+    /// Perform the same checks as `validate`, but return every failure rather
+    /// than only the first. The range checks are only made if all prices are
+    /// in the same currency.
+    pub fn validate_all(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let currency = self.average.0.currency;
+        let same_currency = self.high.0.currency == currency && self.low.0.currency == currency;
+        if !same_currency {
+            errors.push(ValidationError::CurrencyMismatch);
+        }
+        if self.high.0.minor_amount() < 0
+            || self.low.0.minor_amount() < 0
+            || self.average.0.minor_amount() < 0
+        {
+            errors.push(ValidationError::NegativeValue);
+        }
+        if same_currency {
+            if self.low > self.high {
+                errors.push(ValidationError::LowAboveHigh);
+            }
+            if self.average < self.low || self.average > self.high {
+                errors.push(ValidationError::AverageOutOfRange);
+            }
+        }
+        as_validation_result(errors)
+    }
+
+    /// Calculate the fractional upside of the `average` target relative to the
+    /// `current` price, so `0.1` is 10% above current. Returns `None` if the
+    /// current price is zero or in a different currency, see `pct_change`.
+    pub fn upside(&self, current: PerShare) -> Option<f64> {
+        pct_change(current.0, self.average.0)
+    }
+
+    /// Return a stable fingerprint of all the fields of the target; equal
+    /// values always have the same fingerprint, across process runs and
+    /// platforms.
+    pub fn fingerprint(&self) -> u64 {
+        let mut fp = Fingerprint::new();
+        fp.write_money(self.high.0);
+        fp.write_money(self.low.0);
+        fp.write_money(self.average.0);
+        fp.write(&self.number_of_analysts.to_le_bytes());
+        fp.finish()
+    }
+
+    /// Adjust the target for a stock split, dividing each price by `ratio`, the
+    /// number of new shares for each existing share; see `Split`. The target is
+    /// returned unchanged if `ratio` is not positive or not finite, or if the
+    /// adjusted prices are too large to represent.
+    pub fn split_adjust(&self, ratio: f64) -> PriceTarget {
+        if !ratio.is_finite() || ratio <= 0.0 {
+            return self.clone();
+        }
+        let adjust = |value: PerShare| {
+            try_money_from_f64(value.0.currency, value.0.as_f64() / ratio).map(PerShare)
+        };
+        match (adjust(self.high), adjust(self.low), adjust(self.average)) {
+            (Some(high), Some(low), Some(average)) => PriceTarget {
+                high,
+                low,
+                average,
+                number_of_analysts: self.number_of_analysts,
+            },
+            _ => self.clone(),
+        }
+    }
+
+    /// Return the target value selected by `which`.
+    pub fn bound(&self, which: TargetBound) -> PerShare {
+        match which {
+            TargetBound::High => self.high,
+            TargetBound::Low => self.low,
+            TargetBound::Average => self.average,
+        }
+    }
+
+    /// Return where the target value selected by `which` lies within the range
+    /// from `low_52w` to `high_52w`, clamped to `0.0..=1.0`. If the range is
+    /// empty the result is `0.0` or `1.0` for values below or above it, and
+    /// `0.5` for a value equal to it.
+    pub fn position_in_range(
+        &self,
+        low_52w: PerShare,
+        high_52w: PerShare,
+        which: TargetBound,
+    ) -> f64 {
+        let value = self.bound(which).0.as_f64();
+        let (low, high) = (low_52w.0.as_f64(), high_52w.0.as_f64());
+        if high > low {
+            ((value - low) / (high - low)).clamp(0.0, 1.0)
+        } else if value > high {
+            1.0
+        } else if value < low {
+            0.0
+        } else {
+            0.5
+        }
+    }
+
+    /// Return the spread of the target, `high - low`.
+    pub fn spread(&self) -> PerShare {
+        PerShare(self.high.0 - self.low.0)
+    }
+
+    /// A measure, in the range `0.0..=1.0`, of the agreement between analysts
+    /// derived from the spread relative to the average, calculated as
+    /// `1.0 - clamp(spread / average, 0.0, 1.0)`. A zero spread is full agreement,
+    /// `1.0`, and a spread that is as wide as the average, or a zero average with
+    /// any spread, is no agreement, `0.0`.
+    pub fn agreement(&self) -> f64 {
+        let spread = self.spread().0.as_f64();
+        if spread == 0.0 {
+            1.0
+        } else {
+            safe_div(spread, self.average.0.as_f64())
+                .map(|ratio| 1.0 - ratio.clamp(0.0, 1.0))
+                .unwrap_or(0.0)
+        }
+    }
+
+    /// A conviction-adjusted measure of the expected return, the `upside` from
+    /// `current` divided by the relative dispersion of the target,
+    /// `spread / average`; so a large upside with close agreement between
+    /// analysts has a high ratio. Returns `None` if the upside cannot be
+    /// calculated, the spread or average is zero, or the prices are in
+    /// different currencies.
+    pub fn target_information_ratio(&self, current: PerShare) -> Option<f64> {
+        if self.high.0.currency != self.low.0.currency {
+            return None;
+        }
+        let dispersion = safe_div(self.spread().0.as_f64(), self.average.0.as_f64())?;
+        safe_div(self.upside(current)?, dispersion)
+    }
+
+    /// Return a risk-adjusted target that shrinks the `average` towards `current`
+    /// when analysts disagree or coverage is thin. The result is
+    /// `current + w * (average - current)` where the weight
+    /// `w = agreement() * n / (n + shrink_k)` and `n` is `number_of_analysts`;
+    /// so larger values of `shrink_k` require more analysts before the average
+    /// is trusted. A negative `shrink_k` is treated as zero. Returns `None` if
+    /// `current` and the target prices are not all in the same currency.
+    pub fn risk_adjusted_target(&self, current: PerShare, shrink_k: f64) -> Option<PerShare> {
+        let currency = self.average.0.currency;
+        if [self.high, self.low, current]
+            .iter()
+            .any(|price| price.0.currency != currency)
+        {
+            return None;
+        }
+        let n = f64::from(self.number_of_analysts);
+        let weight = self.agreement() * safe_div(n, n + shrink_k.max(0.0)).unwrap_or(0.0);
+        let current_value = current.0.as_f64();
+        Some(PerShare(money_from_f64(
+            currency,
+            current_value + weight * (self.average.0.as_f64() - current_value),
+        )))
+    }
+
+    /// Return a new target with each price reduced by the fractional `discount`,
+    /// that is multiplied by `1.0 - discount`; a discount of `0.0` returns an
+    /// equivalent target. As each price is scaled by the same factor a valid
+    /// target remains valid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `discount` is not in the range `0.0..1.0`.
+    pub fn with_margin_of_safety(&self, discount: f64) -> PriceTarget {
+        assert!(
+            (0.0..1.0).contains(&discount),
+            "margin of safety discount must be in the range 0.0..1.0"
+        );
+        let factor = 1.0 - discount;
+        PriceTarget {
+            high: PerShare(self.high.0 * factor),
+            low: PerShare(self.low.0 * factor),
+            average: PerShare(self.average.0 * factor),
+            number_of_analysts: self.number_of_analysts,
+        }
+    }
+
+    /// Convert all prices in this target to the currency `to`, using the rates
+    /// in effect `on` the given date.
+    pub fn convert(
+        &self,
+        to: Currency,
+        rates: &impl ExchangeRates,
+        on: Date,
+    ) -> RequestResult<PriceTarget> {
+        Ok(PriceTarget {
+            high: PerShare(self.high.0.convert(to, rates, on)?),
+            low: PerShare(self.low.0.convert(to, rates, on)?),
+            average: PerShare(self.average.0.convert(to, rates, on)?),
+            number_of_analysts: self.number_of_analysts,
+        })
+    }
+}
+
+impl AnalystData {
+    /// Return a builder for assembling data incrementally.
+    pub fn builder() -> AnalystDataBuilder {
+        Default::default()
+    }
+}
+
+impl AnalystDataBuilder {
+    /// Set the price target, unless the builder already has a more recent one.
+    pub fn with_target(mut self, target: Snapshot<PriceTarget>) -> Self {
+        match &self.data.target {
+            Some(existing) if existing.date > target.date => (),
+            _ => self.data.target = Some(target),
+        }
+        self
+    }
+
+    /// Add a period of consensus ratings.
+    pub fn add_rating_period(mut self, ratings: Bounded<Ratings>) -> Self {
+        self.data.ratings.push(ratings);
+        self
+    }
+
+    /// Add a consensus EPS value.
+    pub fn add_eps(mut self, eps: EPSConsensus) -> Self {
+        self.data.eps.push(eps);
+        self
+    }
+
+    /// Return the assembled data.
+    pub fn build(self) -> AnalystData {
+        self.data
+    }
+}
+
+/// The key used by `sector_consensus` for symbols with no sector classification.
+pub const UNCLASSIFIED_SECTOR: &str = "Unclassified";
+
+/// The largest number of buckets that `target_histogram` will return.
+pub const MAX_HISTOGRAM_BUCKETS: usize = 10_000;
+
+/// The version of the JSON document written by `AnalystData::to_json`.
+#[cfg(feature = "serde")]
+pub const ANALYST_DATA_SCHEMA_VERSION: u32 = 1;
+
+#[cfg(feature = "serde")]
+impl AnalystData {
+    /// Write the data as a JSON document; an object with the fields of this
+    /// struct and a `schema_version` of `ANALYST_DATA_SCHEMA_VERSION`.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(&AnalystDataDocument {
+            schema_version: ANALYST_DATA_SCHEMA_VERSION,
+            data: self,
+        })
+    }
+
+    /// Read the data from a JSON document written by `to_json`. Unknown fields
+    /// are ignored, but a document with a different `schema_version` is an error.
+    pub fn from_json(json: &str) -> serde_json::Result<AnalystData> {
+        let document: OwnedAnalystDataDocument = serde_json::from_str(json)?;
+        if document.schema_version == ANALYST_DATA_SCHEMA_VERSION {
+            Ok(document.data)
+        } else {
+            Err(serde::de::Error::custom(format!(
+                "unsupported schema_version {}, expected {}",
+                document.schema_version, ANALYST_DATA_SCHEMA_VERSION
+            )))
+        }
+    }
+}
+
+impl DividendEstimate {
+    /// Calculate the implied annual yield at `price`, annualizing `amount` as
+    /// one of `periods_per_year` payments; so a quarterly estimate uses `4`.
+    /// Returns `0.0` if the price is zero or in a different currency.
+    pub fn yield_on(&self, price: PerShare, periods_per_year: u32) -> f64 {
+        if price.0.currency != self.amount.0.currency {
+            0.0
+        } else {
+            safe_div(
+                self.amount.0.as_f64() * f64::from(periods_per_year),
+                price.0.as_f64(),
             )
+            .unwrap_or(0.0)
+        }
+    }
+}
+
+impl ConsensusStore {
+    /// Construct a new, empty, store.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Record an EPS consensus value for `symbol`.
+    pub fn record(&mut self, symbol: Symbol, eps: EPSConsensus) {
+        let series = self.eps.entry(symbol).or_default();
+        let start = series.partition_point(|e| e.published < eps.published);
+        let end = series.partition_point(|e| e.published <= eps.published);
+        if !series[start..end].contains(&eps) {
+            series.insert(end, eps);
+        }
+    }
+
+    /// Record an EPS consensus value for `symbol`, returning the change from the
+    /// most recent consensus for the same fiscal period published on or before
+    /// it, or `None` if there is no such consensus.
+    pub fn record_and_diff(&mut self, symbol: Symbol, eps: EPSConsensus) -> Option<EpsChange> {
+        let change = self.eps.get(&symbol).and_then(|series| {
+            series
+                .iter()
+                .take_while(|e| e.published <= eps.published)
+                .filter(|e| e.fiscal_period == eps.fiscal_period)
+                .last()
+                .map(|prior| EpsChange {
+                    fiscal_period: eps.fiscal_period.clone(),
+                    previous: prior.consensus,
+                    current: eps.consensus,
+                    delta: money_delta(eps.consensus.0, prior.consensus.0).map(PerShare),
+                })
+        });
+        self.record(symbol, eps);
+        change
+    }
+
+    /// Record a price target for `symbol`.
+    pub fn record_target(&mut self, symbol: Symbol, target: Snapshot<PriceTarget>) {
+        let series = self.targets.entry(symbol).or_default();
+        let start = series.partition_point(|t| t.date < target.date);
+        let end = series.partition_point(|t| t.date <= target.date);
+        if !series[start..end]
+            .iter()
+            .any(|t| t.attribution() == target.attribution() && t.data == target.data)
+        {
+            series.insert(end, target);
+        }
+    }
+
+    /// Record a price target for `symbol`, returning the change, as reported by
+    /// `diff_targets` with `threshold`, from the most recent target dated on or
+    /// before it; so `TargetChange::Incomparable` if the prior target is in a
+    /// different currency. The target is recorded in either case.
+    pub fn record_target_and_diff(
+        &mut self,
+        symbol: Symbol,
+        target: Snapshot<PriceTarget>,
+        threshold: PerShare,
+    ) -> TargetChange {
+        let prior = self.targets.get(&symbol).and_then(|series| {
+            series
+                .iter()
+                .take_while(|t| t.date <= target.date)
+                .last()
+                .map(|t| t.data.clone())
+        });
+        let change = diff_targets(prior.as_ref(), Some(&target.data), threshold);
+        self.record_target(symbol, target);
+        change
+    }
+
+    /// Record a consensus rating for `symbol`.
+    pub fn record_ratings(&mut self, symbol: Symbol, r: Bounded<Ratings>) {
+        let series = self.ratings.entry(symbol).or_default();
+        let start = series.partition_point(|b| b.start_date < r.start_date);
+        let end = series.partition_point(|b| b.start_date <= r.start_date);
+        if !series[start..end].iter().any(|b| {
+            b.end_date == r.end_date && b.attribution() == r.attribution() && b.data == r.data
+        }) {
+            series.insert(end, r);
+        }
+    }
+
+    /// Return the history recorded for `symbol` between `from` and `to`
+    /// inclusive; EPS consensus values are selected by `published` date and
+    /// ratings by `start_date`. The target is the latest dated in the range.
+    pub fn history(&self, symbol: Symbol, from: Date, to: Date) -> AnalystData {
+        let eps = match self.eps.get(&symbol) {
+            None => Vec::new(),
+            Some(series) => {
+                let start = series.partition_point(|e| e.published < from);
+                let end = series.partition_point(|e| e.published <= to);
+                series[start..end.max(start)].to_vec()
+            }
+        };
+        let ratings = match self.ratings.get(&symbol) {
+            None => Vec::new(),
+            Some(series) => {
+                let start = series.partition_point(|b| b.start_date.date() < from);
+                let end = series.partition_point(|b| b.start_date.date() <= to);
+                series[start..end.max(start)].to_vec()
+            }
+        };
+        let target = self.targets.get(&symbol).and_then(|series| {
+            series
+                .iter()
+                .rev()
+                .find(|t| t.date.date() >= from && t.date.date() <= to)
+                .cloned()
         });
-        f64::from(total) / f64::from(count)
+        AnalystData {
+            target,
+            ratings,
+            eps,
+        }
     }
 }
 
-/// This trait is implemented by providers to return a set of symbols that are expected
-/// to represent peer companies to `for_symbol`. This set of peers could be provided by
-/// the market or the service provider itself.
-pub trait Peers {
-    /// Return a set of peer symbols.
-    fn peers(&self, for_symbol: Symbol) -> RequestResult<Symbols>;
+impl AccuracyTracker {
+    /// Construct a new tracker, with no targets.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Record a `target` to be evaluated against the price realized on `horizon`.
+    pub fn record(&mut self, target: PriceTarget, horizon: Date) {
+        self.pending.push((target, horizon));
+    }
+
+    /// Evaluate all recorded targets with a price in `realized` for their
+    /// horizon and return the accuracy of every target evaluated so far.
+    /// Targets where the realized price is not positive, or is in a different
+    /// currency, cannot be scored and are discarded.
+    pub fn evaluate(&mut self, realized: &HashMap<Date, Money>) -> AccuracyReport {
+        let mut pending = Vec::new();
+        for (target, horizon) in self.pending.drain(..) {
+            match realized.get(&horizon) {
+                None => pending.push((target, horizon)),
+                Some(price) if price.minor_amount() > 0 => {
+                    if let Some(error) = pct_change(*price, target.average.0) {
+                        self.total_error += error.abs();
+                        self.count += 1;
+                        if target.low.0 <= *price && *price <= target.high.0 {
+                            self.hits += 1;
+                        }
+                    }
+                }
+                Some(_) => (),
+            }
+        }
+        self.pending = pending;
+        self.report()
+    }
+
+    /// Return the accuracy of every target evaluated so far.
+    pub fn report(&self) -> AccuracyReport {
+        let count = self.count as f64;
+        AccuracyReport {
+            mae: safe_div(self.total_error, count).unwrap_or(0.0),
+            hit_rate: safe_div(self.hits as f64, count).unwrap_or(0.0),
+            count: self.count,
+        }
+    }
+
+    /// Return the number of recorded targets not yet evaluated.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
 }
 
-/// This trait is implemented by providers to return various analyst recommendations.
-pub trait AnalystRecommendations {
-    /// Return the target price recommendations for the symbol
-    fn target_price(&self, for_symbol: Symbol) -> RequestResult<Snapshot<PriceTarget>>;
+impl<P: AnalystRecommendations> Wrapped<P> {
+    /// Wrap `provider`, applying `policy` to all requests.
+    pub fn new(provider: P, policy: ProviderPolicy) -> Self {
+        Wrapped {
+            provider,
+            policy,
+            state: Mutex::new(WrappedState::default()),
+        }
+    }
 
-    /// Return the consensus ratings for the symbol
-    fn consensus_rating(&self, for_symbol: Symbol) -> RequestResult<Vec<Bounded<Ratings>>>;
+    /// Return a reference to the wrapped provider.
+    pub fn inner(&self) -> &P {
+        &self.provider
+    }
 
-    /// Return the consensus earnings per share (EPS) for the symbol
-    fn consensus_eps(&self, for_symbol: Symbol) -> RequestResult<Vec<EPSConsensus>>;
+    /// Return the policy applied to requests.
+    pub fn policy(&self) -> &ProviderPolicy {
+        &self.policy
+    }
+
+    /// Remove all cached results.
+    pub fn clear_cache(&self) {
+        self.lock_state().cache.clear();
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, WrappedState> {
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn request<T, R>(
+        &self,
+        key: CacheKey,
+        cached: fn(&CachedValue) -> Option<T>,
+        to_cache: fn(T) -> CachedValue,
+        request: R,
+    ) -> RequestResult<T>
+    where
+        T: Clone,
+        R: Fn(&P) -> RequestResult<T>,
+    {
+        if let Some(ttl) = self.policy.ttl {
+            if let Some((at, value)) = self.lock_state().cache.get(&key) {
+                if at.elapsed() < ttl {
+                    if let Some(value) = cached(value) {
+                        return Ok(value);
+                    }
+                }
+            }
+        }
+        let attempts = self.policy.max_attempts.max(1);
+        let mut attempt = 1;
+        let result = loop {
+            self.wait_for_interval();
+            match request(&self.provider) {
+                Err(RequestError::CommunicationError) | Err(RequestError::RequestThrottled)
+                    if attempt < attempts =>
+                {
+                    thread::sleep(self.policy.backoff_base * 2u32.saturating_pow(attempt - 1));
+                    attempt += 1;
+                }
+                result => break result,
+            }
+        };
+        if let (Some(_), Ok(value)) = (self.policy.ttl, &result) {
+            self.lock_state()
+                .cache
+                .insert(key, (Instant::now(), to_cache(value.clone())));
+        }
+        result
+    }
+
+    fn wait_for_interval(&self) {
+        if let Some(interval) = self.policy.min_interval {
+            let now = Instant::now();
+            let start = {
+                let mut state = self.lock_state();
+                let start = match state.next_request {
+                    Some(next) if next > now => next,
+                    _ => now,
+                };
+                state.next_request = Some(start + interval);
+                start
+            };
+            if start > now {
+                thread::sleep(start - now);
+            }
+        }
+    }
+}
+
+impl<P: AnalystRecommendations> AnalystRecommendations for Wrapped<P> {
+    fn target_price(&self, for_symbol: Symbol) -> RequestResult<Snapshot<PriceTarget>> {
+        self.request(
+            CacheKey::Target(for_symbol.clone()),
+            |value| match value {
+                CachedValue::Target(target) => Some(target.clone()),
+                _ => None,
+            },
+            CachedValue::Target,
+            |p| p.target_price(for_symbol.clone()),
+        )
+    }
+
+    fn consensus_rating(&self, for_symbol: Symbol) -> RequestResult<Vec<Bounded<Ratings>>> {
+        self.request(
+            CacheKey::Ratings(for_symbol.clone()),
+            |value| match value {
+                CachedValue::Ratings(ratings) => Some(ratings.clone()),
+                _ => None,
+            },
+            CachedValue::Ratings,
+            |p| p.consensus_rating(for_symbol.clone()),
+        )
+    }
+
+    fn consensus_eps(&self, for_symbol: Symbol) -> RequestResult<Vec<EPSConsensus>> {
+        self.request(
+            CacheKey::Eps(for_symbol.clone()),
+            |value| match value {
+                CachedValue::Eps(eps) => Some(eps.clone()),
+                _ => None,
+            },
+            CachedValue::Eps,
+            |p| p.consensus_eps(for_symbol.clone()),
+        )
+    }
+
+    fn consensus_eps_at(
+        &self,
+        for_symbol: Symbol,
+        as_of: Date,
+    ) -> RequestResult<Option<Vec<EPSConsensus>>> {
+        self.request(
+            CacheKey::EpsAt(for_symbol.clone(), as_of),
+            |value| match value {
+                CachedValue::EpsAt(eps) => Some(eps.clone()),
+                _ => None,
+            },
+            CachedValue::EpsAt,
+            |p| p.consensus_eps_at(for_symbol.clone(), as_of),
+        )
+    }
+
+    fn analyst_estimates(&self, for_symbol: Symbol) -> RequestResult<Option<Vec<AnalystEstimate>>> {
+        self.request(
+            CacheKey::Estimates(for_symbol.clone()),
+            |value| match value {
+                CachedValue::Estimates(estimates) => Some(estimates.clone()),
+                _ => None,
+            },
+            CachedValue::Estimates,
+            |p| p.analyst_estimates(for_symbol.clone()),
+        )
+    }
+}
+
+impl ScaleMapping {
+    /// Map `value` from the source scale onto the target scale.
+    pub fn apply(&self, value: f64) -> f64 {
+        self.slope * value + self.intercept
+    }
+}
+
+impl<A, B> MergingRecommendations<A, B>
+where
+    A: AnalystRecommendations,
+    B: AnalystRecommendations,
+{
+    /// Combine the `primary` and `secondary` providers.
+    pub fn new(primary: A, secondary: B) -> Self {
+        MergingRecommendations {
+            primary,
+            secondary,
+            primary_source: None,
+            secondary_source: None,
+        }
+    }
+
+    /// Name the `primary` and `secondary` providers, used as the source of
+    /// any value returned without one.
+    pub fn with_sources(self, primary: impl Into<String>, secondary: impl Into<String>) -> Self {
+        MergingRecommendations {
+            primary_source: Some(primary.into()),
+            secondary_source: Some(secondary.into()),
+            ..self
+        }
+    }
+
+    /// Return a reference to the primary provider.
+    pub fn primary(&self) -> &A {
+        &self.primary
+    }
+
+    /// Return a reference to the secondary provider.
+    pub fn secondary(&self) -> &B {
+        &self.secondary
+    }
+}
+
+impl<A, B> AnalystRecommendations for MergingRecommendations<A, B>
+where
+    A: AnalystRecommendations,
+    B: AnalystRecommendations,
+{
+    fn target_price(&self, for_symbol: Symbol) -> RequestResult<Snapshot<PriceTarget>> {
+        merge_results(
+            self.primary
+                .target_price(for_symbol.clone())
+                .map(|target| snapshot_or_source(target, &self.primary_source)),
+            || {
+                self.secondary
+                    .target_price(for_symbol)
+                    .map(|target| snapshot_or_source(target, &self.secondary_source))
+            },
+            |_| true,
+        )
+    }
+
+    fn consensus_rating(&self, for_symbol: Symbol) -> RequestResult<Vec<Bounded<Ratings>>> {
+        merge_results(
+            self.primary
+                .consensus_rating(for_symbol.clone())
+                .map(|ratings| bounded_or_source(ratings, &self.primary_source)),
+            || {
+                self.secondary
+                    .consensus_rating(for_symbol)
+                    .map(|ratings| bounded_or_source(ratings, &self.secondary_source))
+            },
+            |ratings| !ratings.is_empty(),
+        )
+    }
+
+    fn consensus_eps(&self, for_symbol: Symbol) -> RequestResult<Vec<EPSConsensus>> {
+        merge_results(
+            self.primary
+                .consensus_eps(for_symbol.clone())
+                .map(|eps| eps_or_source(eps, &self.primary_source)),
+            || {
+                self.secondary
+                    .consensus_eps(for_symbol)
+                    .map(|eps| eps_or_source(eps, &self.secondary_source))
+            },
+            |eps| !eps.is_empty(),
+        )
+    }
+
+    fn consensus_eps_at(
+        &self,
+        for_symbol: Symbol,
+        as_of: Date,
+    ) -> RequestResult<Option<Vec<EPSConsensus>>> {
+        merge_results(
+            self.primary
+                .consensus_eps_at(for_symbol.clone(), as_of)
+                .map(|eps| eps.map(|eps| eps_or_source(eps, &self.primary_source))),
+            || {
+                self.secondary
+                    .consensus_eps_at(for_symbol, as_of)
+                    .map(|eps| eps.map(|eps| eps_or_source(eps, &self.secondary_source)))
+            },
+            Option::is_some,
+        )
+    }
+
+    fn analyst_estimates(&self, for_symbol: Symbol) -> RequestResult<Option<Vec<AnalystEstimate>>> {
+        merge_results(
+            self.primary.analyst_estimates(for_symbol.clone()),
+            || self.secondary.analyst_estimates(for_symbol),
+            Option::is_some,
+        )
+    }
+}
+
+/// This trait is implemented by strategies that score a set of ratings, allowing
+/// a single scoring methodology to be used by functions such as
+/// `composite_score_with`.
+pub trait RatingScorer {
+    /// Return a score for `ratings` in the range `0.0..=1.0`, where higher is
+    /// more favorable, or `None` if there are no ratings.
+    fn score(&self, ratings: &Ratings) -> Option<f64>;
+}
+
+impl RatingScorer for LinearScorer {
+    fn score(&self, ratings: &Ratings) -> Option<f64> {
+        ratings.outperform_probability()
+    }
+}
+
+impl Default for ExponentialScorer {
+    fn default() -> Self {
+        ExponentialScorer { base: 2.0 }
+    }
+}
+
+impl RatingScorer for ExponentialScorer {
+    fn score(&self, ratings: &Ratings) -> Option<f64> {
+        if self.base <= 1.0 {
+            return LinearScorer.score(ratings);
+        }
+        let max = self.base.powi(4) - 1.0;
+        let (total, count) =
+            ratings
+                .ratings
+                .iter()
+                .fold((0.0, 0.0), |(total, count), (rating, c)| {
+                    let steps = 5 - i32::from(rating.to_code(CodeScale::OneIsBuy));
+                    let c = f64::from(*c);
+                    (total + c * (self.base.powi(steps) - 1.0) / max, count + c)
+                });
+        safe_div(total, count)
+    }
+}
+
+/// This trait is implemented by values that can be validated, reporting all of
+/// their validation errors, and so can be used with `validate_batch`.
+pub trait Validatable {
+    /// Validate the value, returning every failure.
+    fn validate_all(&self) -> Result<(), Vec<ValidationError>>;
+}
+
+impl Validatable for PriceTarget {
+    fn validate_all(&self) -> Result<(), Vec<ValidationError>> {
+        PriceTarget::validate_all(self)
+    }
+}
+
+impl Validatable for EPSConsensus {
+    fn validate_all(&self) -> Result<(), Vec<ValidationError>> {
+        EPSConsensus::validate_all(self)
+    }
+}
+
+/// This trait is implemented by providers to return the corporate actions, such
+/// as splits, that invalidate previously retrieved targets; see
+/// `adjust_target_for_actions`.
+pub trait CorporateActions {
+    /// Return the splits for the symbol that take effect between `from` and
+    /// `to`, inclusive.
+    fn splits(&self, for_symbol: Symbol, from: Date, to: Date) -> RequestResult<Vec<Split>>;
+}
+
+/// This trait is implemented by providers to return a set of symbols that are expected
+/// to represent peer companies to `for_symbol`. This set of peers could be provided by
+/// the market or the service provider itself.
+pub trait Peers {
+    /// Return a set of peer symbols.
+    fn peers(&self, for_symbol: Symbol) -> RequestResult<Symbols>;
+
+    /// Return peer symbols with a relevance score in the range `0.0..=1.0`,
+    /// sorted with the most similar peers first.
+    ///
+    /// The default implementation simply scores every result of `peers` as
+    /// `1.0`; these scores are uninformative and callers should not rely on
+    /// them unless the provider documents its own implementation.
+    fn scored_peers(&self, for_symbol: Symbol) -> RequestResult<Vec<(Symbol, f64)>> {
+        let mut scored: Vec<(Symbol, f64)> = self
+            .peers(for_symbol)?
+            .into_iter()
+            .map(|symbol| (symbol, 1.0))
+            .collect();
+        scored.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        Ok(scored)
+    }
+}
+
+/// This trait is implemented by providers whose peers endpoint returns results
+/// in pages. Such a provider will usually also implement `Peers`, where `peers`
+/// may simply call `all_peers` to drain all pages; consumers wanting to stop
+/// early can use `pages` instead.
+pub trait PagedPeers {
+    /// Return a single page of peer symbols, starting at `cursor` (or the
+    /// first page if `None`), along with the cursor for the next page, if any.
+    fn peers_page(
+        &self,
+        for_symbol: Symbol,
+        cursor: Option<String>,
+    ) -> RequestResult<(Symbols, Option<String>)>;
+
+    /// Return a lazy iterator over the pages of peer symbols.
+    fn pages(&self, for_symbol: Symbol) -> PeerPages<'_, Self> {
+        PeerPages {
+            provider: self,
+            for_symbol,
+            cursor: None,
+            done: false,
+        }
+    }
+
+    /// Return the peer symbols from all pages, failing with the first error.
+    fn all_peers(&self, for_symbol: Symbol) -> RequestResult<Symbols> {
+        let mut all = Symbols::new();
+        for page in self.pages(for_symbol) {
+            all.extend(page?);
+        }
+        Ok(all)
+    }
+}
+
+impl<'a, P: PagedPeers + ?Sized> Iterator for PeerPages<'a, P> {
+    type Item = RequestResult<Symbols>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self
+            .provider
+            .peers_page(self.for_symbol.clone(), self.cursor.take())
+        {
+            Ok((symbols, cursor)) => {
+                self.done = cursor.is_none();
+                self.cursor = cursor;
+                Some(Ok(symbols))
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// This trait is implemented by providers to return various analyst recommendations.
+pub trait AnalystRecommendations {
+    /// Return the target price recommendations for the symbol
+    fn target_price(&self, for_symbol: Symbol) -> RequestResult<Snapshot<PriceTarget>>;
+
+    /// Return the consensus ratings for the symbol.
+    ///
+    /// A symbol that is not covered by the provider returns
+    /// `RequestError::BadSymbolError`, while a symbol that is covered but has no
+    /// current ratings returns an empty `Vec`; see `coverage_status`.
+    fn consensus_rating(&self, for_symbol: Symbol) -> RequestResult<Vec<Bounded<Ratings>>>;
+
+    /// Return the consensus earnings per share (EPS) for the symbol
+    fn consensus_eps(&self, for_symbol: Symbol) -> RequestResult<Vec<EPSConsensus>>;
+
+    /// Return the consensus earnings per share (EPS) for the symbol as known
+    /// `as_of` the given date, avoiding look-ahead when back-testing. Returns
+    /// `None` if no estimates had been published on or before `as_of`.
+    ///
+    /// The default implementation filters the results of `consensus_eps` by
+    /// their `published` date; providers with access to historical estimates
+    /// should override this.
+    fn consensus_eps_at(
+        &self,
+        for_symbol: Symbol,
+        as_of: Date,
+    ) -> RequestResult<Option<Vec<EPSConsensus>>> {
+        let known: Vec<EPSConsensus> = self
+            .consensus_eps(for_symbol)?
+            .into_iter()
+            .filter(|eps| eps.published <= as_of)
+            .collect();
+        if known.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(known))
+        }
+    }
+
+    /// Return the individual analyst estimates that make up the consensus values
+    /// for the symbol, or `None` if there are none.
+    ///
+    /// The default implementation returns `RequestError::Unsupported`.
+    fn analyst_estimates(&self, for_symbol: Symbol) -> RequestResult<Option<Vec<AnalystEstimate>>> {
+        let _ = for_symbol;
+        Err(RequestError::Unsupported)
+    }
+
+    /// Return the change in consensus rating for the symbol from the consensus
+    /// as of `since`, the latest period starting on or before that date, to the
+    /// current consensus, or `None` if there is no history before `since` or the
+    /// `scaled_average` has not changed.
+    ///
+    /// The default implementation compares periods from `consensus_rating`.
+    fn rating_change_since(
+        &self,
+        for_symbol: Symbol,
+        since: Date,
+    ) -> RequestResult<Option<RatingChangeEvent>> {
+        let history = self.consensus_rating(for_symbol)?;
+        let mut periods: Vec<&Bounded<Ratings>> =
+            history.iter().filter(|r| !r.data.is_empty()).collect();
+        periods.sort_by_key(|r| r.start_date);
+        let then = periods.iter().rev().find(|r| r.start_date.date() <= since);
+        Ok(match (then, periods.last()) {
+            (Some(then), Some(now)) => {
+                rating_change(&then.data, &now.data, now.start_date.date(), 0.0)
+            }
+            _ => None,
+        })
+    }
+
+    /// Return the consensus ratings for a number of symbols, tolerating failures
+    /// for individual symbols.
+    ///
+    /// * a symbol with ratings maps to `Ok` with those ratings,
+    /// * a symbol whose request failed maps to `Err` with that error,
+    /// * a symbol with no ratings (an empty result) is absent from the map.
+    ///
+    /// If the request for the first symbol returns `RequestError::Unsupported`
+    /// the provider is taken not to support ratings at all, and the call as a
+    /// whole fails with that error; for any later symbol it is an error for
+    /// that symbol only, as a provider may support ratings for some symbols,
+    /// or exchanges, and not others. The default implementation simply calls
+    /// `consensus_rating` for each symbol in turn.
+    fn consensus_rating_many(
+        &self,
+        symbols: &[Symbol],
+    ) -> RequestResult<HashMap<Symbol, RequestResult<Vec<Bounded<Ratings>>>>> {
+        let mut results = HashMap::new();
+        for (index, symbol) in symbols.iter().enumerate() {
+            match self.consensus_rating(symbol.clone()) {
+                Err(RequestError::Unsupported) if index == 0 => {
+                    return Err(RequestError::Unsupported)
+                }
+                Ok(ratings) if ratings.is_empty() => (),
+                result => {
+                    results.insert(symbol.clone(), result);
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Return the most recent consensus ratings period for the symbol, the one
+    /// with the latest start date, or `None` if there are no ratings. As there
+    /// are then no ratings either, a symbol that is not covered or a provider
+    /// that does not support ratings also returns `None`, rather than
+    /// `RequestError::BadSymbolError` or `RequestError::Unsupported`.
+    ///
+    /// The default implementation selects the period from `consensus_rating`.
+    fn latest_consensus_rating(
+        &self,
+        for_symbol: Symbol,
+    ) -> RequestResult<Option<Bounded<Ratings>>> {
+        Ok(absent_as_none(self.consensus_rating(for_symbol))?
+            .unwrap_or_default()
+            .into_iter()
+            .max_by_key(|r| r.start_date))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Summarize the analyst coverage of `symbols` by requesting the price target,
+/// consensus ratings, and consensus EPS of each. A symbol is not covered by a
+/// request that returns no data, a target with no analysts, or fails with
+/// `RequestError::BadSymbolError` or `RequestError::Unsupported`; any other
+/// error fails the call as a whole. The `avg_analysts` is `0.0` if no symbol
+/// has a target.
+pub fn coverage_stats(
+    provider: &impl AnalystRecommendations,
+    symbols: &Symbols,
+) -> RequestResult<CoverageStats> {
+    let mut stats = CoverageStats::default();
+    let mut analysts = 0u64;
+    for symbol in symbols {
+        let target = absent_as_none(provider.target_price(symbol.clone()))?
+            .filter(|target| target.data.number_of_analysts > 0);
+        let ratings = absent_as_none(provider.consensus_rating(symbol.clone()))?
+            .filter(|ratings| !ratings.is_empty());
+        let eps =
+            absent_as_none(provider.consensus_eps(symbol.clone()))?.filter(|eps| !eps.is_empty());
+        if let Some(target) = &target {
+            stats.with_targets += 1;
+            analysts += u64::from(target.data.number_of_analysts);
+        }
+        if ratings.is_some() {
+            stats.with_ratings += 1;
+        }
+        if eps.is_some() {
+            stats.with_eps += 1;
+        }
+        if target.is_none() && ratings.is_none() && eps.is_none() {
+            stats.uncovered += 1;
+        }
+    }
+    stats.avg_analysts = safe_div(analysts as f64, stats.with_targets as f64).unwrap_or(0.0);
+    Ok(stats)
+}
+
+/// Return the coverage status of a request result, such as from
+/// `AnalystRecommendations::consensus_rating`, where `None` means the symbol is
+/// not covered by the provider, reported by the request traits as
+/// `RequestError::BadSymbolError`, and an empty `Vec` means the symbol is
+/// covered but has no data.
+pub fn coverage_status<T>(r: &Option<Vec<T>>) -> CoverageStatus {
+    match r {
+        None => CoverageStatus::NotCovered,
+        Some(values) if values.is_empty() => CoverageStatus::CoveredNoData,
+        Some(_) => CoverageStatus::CoveredWithData,
+    }
+}
+
+/// Return the coverage status of a request result, such as from
+/// `AnalystRecommendations::consensus_rating`, following the contract of the
+/// request traits; only `RequestError::BadSymbolError` means that the symbol is
+/// not covered. Any other error, including `RequestError::Unsupported`, says
+/// nothing about coverage and so returns `None`.
+pub fn coverage_status_of<T>(r: &RequestResult<Vec<T>>) -> Option<CoverageStatus> {
+    match r {
+        Ok(values) if values.is_empty() => Some(CoverageStatus::CoveredNoData),
+        Ok(_) => Some(CoverageStatus::CoveredWithData),
+        Err(RequestError::BadSymbolError(_)) => Some(CoverageStatus::NotCovered),
+        Err(_) => None,
+    }
+}
+
+/// Estimate the next reporting date, for feeds that provide fiscal end dates
+/// but omit the forward report date. The typical reporting interval is taken
+/// as the median gap between `past_end_dates` and projected forward from
+/// `last_report`; fewer than two distinct historical dates returns `None`.
+pub fn estimate_next_report(past_end_dates: &[Date], last_report: Date) -> Option<Date> {
+    let mut dates = past_end_dates.to_vec();
+    dates.sort();
+    dates.dedup();
+    if dates.len() < 2 {
+        return None;
+    }
+    let mut gaps: Vec<i64> = dates
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).num_days())
+        .collect();
+    gaps.sort();
+    let median = (gaps[(gaps.len() - 1) / 2] + gaps[gaps.len() / 2]) / 2;
+    last_report.checked_add_signed(chrono::Duration::days(median))
+}
+
+/// Build a chronological timeline of all the analyst data for a symbol, ordered
+/// by `TimelineEvent::date`. `Bounded` ratings periods are ordered by their start
+/// date, and events with the same date retain the order target, ratings, EPS.
+pub fn build_timeline(data: &AnalystData) -> Vec<TimelineEvent> {
+    let mut timeline: Vec<TimelineEvent> = data
+        .target
+        .iter()
+        .cloned()
+        .map(TimelineEvent::Target)
+        .chain(data.ratings.iter().cloned().map(TimelineEvent::Rating))
+        .chain(data.eps.iter().cloned().map(TimelineEvent::Eps))
+        .collect();
+    timeline.sort_by_key(|event| event.date());
+    timeline
+}
+
+/// Reconstruct the distribution of ratings from a feed of individual rating
+/// changes. Events are applied in date order up to, and including, `as_of`;
+/// each change decrements the count for `from` (if any) and increments the
+/// count for `to`. The returned ratings have no `scale_mark`.
+pub fn reconstruct_ratings(events: &[RatingEvent], as_of: Date) -> Ratings {
+    let mut sorted: Vec<&RatingEvent> = events.iter().filter(|e| e.date <= as_of).collect();
+    sorted.sort_by_key(|e| e.date);
+    let mut ratings: HashMap<RatingType, Counter> = HashMap::new();
+    for event in sorted {
+        if let Some(from) = event.from {
+            let count = ratings.entry(from).or_insert(0);
+            *count = count.saturating_sub(1);
+        }
+        *ratings.entry(event.to).or_insert(0) += 1;
+    }
+    Ratings {
+        ratings,
+        scale_mark: None,
+    }
+}
+
+/// Compare the `average` of two price targets, usually successive snapshots, and
+/// return the kind of change. A change in average no larger than `threshold` is
+/// reported as `TargetChange::Unchanged`. The delta carried by `Raised` and
+/// `Lowered` is `curr.average - prev.average`. If the two targets, and the
+/// threshold, are not in the same currency `TargetChange::Incomparable` is
+/// returned.
+pub fn diff_targets(
+    prev: Option<&PriceTarget>,
+    curr: Option<&PriceTarget>,
+    threshold: PerShare,
+) -> TargetChange {
+    match (prev, curr) {
+        (None, None) => TargetChange::Unchanged,
+        (None, Some(_)) => TargetChange::Initiated,
+        (Some(_), None) => TargetChange::Dropped,
+        (Some(prev), Some(curr)) => {
+            let currency = prev.average.0.currency;
+            if curr.average.0.currency != currency || threshold.0.currency != currency {
+                return TargetChange::Incomparable;
+            }
+            let delta = match curr.average.0.checked_sub(prev.average.0) {
+                None => return TargetChange::Incomparable,
+                Some(delta) => delta,
+            };
+            if i64::from(delta.minor_amount()).abs() <= i64::from(threshold.0.minor_amount()) {
+                TargetChange::Unchanged
+            } else if delta.minor_amount() > 0 {
+                TargetChange::Raised(PerShare(delta))
+            } else {
+                TargetChange::Lowered(PerShare(delta))
+            }
+        }
+    }
+}
+
+/// Check that consensus EPS does not decrease into later fiscal periods. The
+/// series is ordered by `fiscal_end_date` and each pair of successive periods
+/// where the later `consensus` is below the earlier one is returned as
+/// `(earlier, later)`. An empty result means the series is non-decreasing.
+pub fn check_eps_monotonic(series: &[EPSConsensus]) -> Vec<(FinancialPeriod, FinancialPeriod)> {
+    let mut sorted: Vec<&EPSConsensus> = series.iter().collect();
+    sorted.sort_by_key(|eps| eps.fiscal_end_date);
+    sorted
+        .windows(2)
+        .filter(|pair| pair[1].consensus < pair[0].consensus)
+        .map(|pair| (pair[0].fiscal_period.clone(), pair[1].fiscal_period.clone()))
+        .collect()
+}
+
+/// Remove any stale estimates, see `EPSConsensus::is_stale`, from `series`.
+pub fn prune_stale(series: Vec<EPSConsensus>, today: Date) -> Vec<EPSConsensus> {
+    series
+        .into_iter()
+        .filter(|eps| !eps.is_stale(today))
+        .collect()
+}
+
+/// Calculate the forward trailing-twelve-month (TTM) EPS, the sum of the
+/// consensus for the four consecutive quarters starting at `from`. Where the
+/// series has more than one consensus for a quarter the latest published is
+/// used. Non-quarterly periods, and periods in a different fiscal calendar to
+/// the `from` quarter, are ignored.
+///
+/// Returns `None` if `from` is not a quarter, any of the four quarters is
+/// missing, or the values are in different currencies.
+pub fn forward_ttm_eps(series: &[EPSConsensus], from: FinancialPeriod) -> Option<PerShare> {
+    if !matches!(from, FinancialPeriod::Quarter { .. }) {
+        return None;
+    }
+    let first = series
+        .iter()
+        .filter(|eps| eps.fiscal_period == from)
+        .max_by_key(|eps| eps.published)?;
+    let currency = first.consensus.0.currency;
+    let mut total = first.consensus.0;
+    let mut period = from;
+    for _ in 1..4 {
+        period = period.next()?;
+        let eps = series
+            .iter()
+            .filter(|eps| eps.fiscal_period == period)
+            .filter(|eps| eps.fiscal_calendar == first.fiscal_calendar)
+            .max_by_key(|eps| eps.published)?;
+        if eps.consensus.0.currency != currency {
+            return None;
+        }
+        total = total + eps.consensus.0;
+    }
+    Some(PerShare(total))
+}
+
+/// Calculate the breadth of EPS revisions between two sets of estimates, such
+/// as the term structure of consensus fetched a week apart. Estimates are
+/// matched by fiscal period and calendar, using the first estimate for a period
+/// on each side, and the result is `(raised - lowered) / matched`, in the range
+/// `-1.0..=1.0`. Periods present on only one side, or with values in different
+/// currencies, are ignored; returns `0.0` if no periods match.
+pub fn revision_breadth(prev: &[EPSConsensus], curr: &[EPSConsensus]) -> f64 {
+    let same_period = |a: &EPSConsensus, b: &EPSConsensus| {
+        a.fiscal_period == b.fiscal_period && a.fiscal_calendar == b.fiscal_calendar
+    };
+    let (mut matched, mut net) = (0i32, 0i32);
+    for (i, current) in curr.iter().enumerate() {
+        if curr[..i]
+            .iter()
+            .any(|earlier| same_period(earlier, current))
+        {
+            continue;
+        }
+        if let Some(previous) = prev.iter().find(|p| same_period(p, current)) {
+            if previous.consensus.0.currency == current.consensus.0.currency {
+                matched += 1;
+                net += match current
+                    .consensus
+                    .0
+                    .minor_amount()
+                    .cmp(&previous.consensus.0.minor_amount())
+                {
+                    Ordering::Greater => 1,
+                    Ordering::Less => -1,
+                    Ordering::Equal => 0,
+                };
+            }
+        }
+    }
+    safe_div(f64::from(net), f64::from(matched)).unwrap_or(0.0)
+}
+
+/// Remove duplicate estimates from `series`, those with the same fiscal period,
+/// fiscal end date, and consensus value, such as when combining overlapping
+/// requests. Of each set of duplicates the one with the highest number of
+/// estimates is kept, in the position of the first; the order of the series
+/// is otherwise unchanged.
+pub fn dedup_eps(series: Vec<EPSConsensus>) -> Vec<EPSConsensus> {
+    let same = |a: &EPSConsensus, b: &EPSConsensus| {
+        a.fiscal_period == b.fiscal_period
+            && a.fiscal_end_date == b.fiscal_end_date
+            && a.consensus == b.consensus
+    };
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut unique: Vec<EPSConsensus> = Vec::with_capacity(series.len());
+    for eps in series {
+        let candidates = seen.entry(eps_content_fingerprint(&eps)).or_default();
+        match candidates.iter().find(|i| same(&unique[**i], &eps)) {
+            Some(i) => {
+                if eps.number_of_estimates > unique[*i].number_of_estimates {
+                    unique[*i] = eps;
+                }
+            }
+            None => {
+                candidates.push(unique.len());
+                unique.push(eps);
+            }
+        }
+    }
+    unique
+}
+
+/// Calculate the forward price to earnings (P/E) ratio for `price` using the
+/// consensus EPS annualized according to its fiscal period, see
+/// `FinancialPeriod::periods_per_year`; so a quarterly consensus is multiplied
+/// by four. Returns `None` if the consensus is zero or negative, or the price
+/// and consensus are in different currencies.
+pub fn forward_pe(price: PerShare, eps: &EPSConsensus) -> Option<f64> {
+    if eps.is_profitable() && price.0.currency == eps.consensus.0.currency {
+        safe_div(price.0.as_f64(), annualized_eps(eps))
+    } else {
+        None
+    }
+}
+
+/// Calculate the forward P/E ratio implied by the `average` of `target` and the
+/// consensus EPS, as a cross-check of the target against earnings. Returns
+/// `None` if the consensus is zero or negative, or the target and consensus
+/// are in different currencies.
+pub fn implied_forward_pe(target: &PriceTarget, eps: &EPSConsensus) -> Option<f64> {
+    forward_pe(target.average, eps)
+}
+
+/// Calculate the range of forward P/E ratios implied by the `low` and `high` of
+/// `target` and the consensus EPS, as `(low, high)`. Returns `None` in the same
+/// cases as `implied_forward_pe`.
+pub fn implied_pe_range(target: &PriceTarget, eps: &EPSConsensus) -> Option<(f64, f64)> {
+    Some((forward_pe(target.low, eps)?, forward_pe(target.high, eps)?))
+}
+
+/// Calculate the forward earnings yield, the inverse of the P/E ratio, for
+/// `price` using the annualized consensus EPS, as `forward_pe`; a negative
+/// consensus gives a negative yield. Returns `None` if the price is zero, or
+/// the price and consensus are in different currencies.
+pub fn earnings_yield(price: PerShare, eps: &EPSConsensus) -> Option<f64> {
+    if price.0.currency == eps.consensus.0.currency {
+        safe_div(annualized_eps(eps), price.0.as_f64())
+    } else {
+        None
+    }
+}
+
+/// Calculate the fractional growth in consensus EPS between the first two
+/// fiscal periods in `series`, ordered by `fiscal_end_date`; so `0.2` is a
+/// 20% increase. This is intended for a series of like periods (all quarters,
+/// or all years) and returns `None` if there are fewer than two periods or
+/// the earlier consensus is zero. Growth from a negative consensus follows
+/// `pct_change`, so a loss that narrows is positive growth.
+///
+/// Returns `AnalysisError::MixedFiscalCalendars` if the series mixes periods
+/// from different fiscal calendars.
+pub fn eps_growth(series: &[EPSConsensus]) -> Result<Option<f64>, AnalysisError> {
+    check_fiscal_calendars(series)?;
+    let mut sorted: Vec<&EPSConsensus> = series.iter().collect();
+    sorted.sort_by_key(|eps| eps.fiscal_end_date);
+    Ok(match sorted.as_slice() {
+        [earlier, later, ..] => pct_change(earlier.consensus.0, later.consensus.0),
+        _ => None,
+    })
+}
+
+/// Consolidate a series of EPS consensus values, usually merged from a number of
+/// sources, into a single value per fiscal period ordered by `fiscal_end_date`.
+/// Values for the same period are combined into a consensus weighted by the
+/// `number_of_estimates` of each, where a value with no estimates has a weight
+/// of one. The estimate counts are summed, saturating at the largest `Counter`,
+/// the estimate range is the widest of the known ranges, and the latest report
+/// and published dates are kept. The attribution is kept only if all of the
+/// combined values share it.
+///
+/// Returns `AnalysisError::MixedFiscalCalendars` if the series mixes periods
+/// from different fiscal calendars, and `AnalysisError::MixedCurrencies` if the
+/// values are not all in the same currency.
+pub fn consolidate_eps(series: &[EPSConsensus]) -> Result<Vec<EPSConsensus>, AnalysisError> {
+    check_fiscal_calendars(series)?;
+    if let Some(first) = series.first() {
+        let currency = first.consensus.0.currency;
+        if series.iter().any(|eps| {
+            [Some(eps.consensus), eps.high_estimate, eps.low_estimate]
+                .iter()
+                .flatten()
+                .any(|value| value.0.currency != currency)
+        }) {
+            return Err(AnalysisError::MixedCurrencies);
+        }
+    }
+    // keep the more extreme of two estimates, `Greater` for high and `Less` for low
+    let widen = |a: Option<PerShare>, b: Option<PerShare>, extreme: Ordering| match (a, b) {
+        (Some(a), Some(b)) if b.partial_cmp(&a) == Some(extreme) => Some(b),
+        (a, b) => a.or(b),
+    };
+    let mut consolidated: Vec<(EPSConsensus, f64, f64)> = Vec::new();
+    for eps in series {
+        let weight = f64::from(eps.number_of_estimates.max(1));
+        let weighted = eps.consensus.0.as_f64() * weight;
+        match consolidated
+            .iter_mut()
+            .find(|(c, _, _)| c.fiscal_period == eps.fiscal_period)
+        {
+            Some((existing, total, total_weight)) => {
+                *total += weighted;
+                *total_weight += weight;
+                existing.number_of_estimates = existing
+                    .number_of_estimates
+                    .saturating_add(eps.number_of_estimates);
+                existing.high_estimate =
+                    widen(existing.high_estimate, eps.high_estimate, Ordering::Greater);
+                existing.low_estimate =
+                    widen(existing.low_estimate, eps.low_estimate, Ordering::Less);
+                existing.next_report_date = existing.next_report_date.max(eps.next_report_date);
+                existing.published = existing.published.max(eps.published);
+                if existing.attribution != eps.attribution {
+                    existing.attribution = None;
+                }
+            }
+            None => consolidated.push((eps.clone(), weighted, weight)),
+        }
+    }
+    let mut consolidated: Vec<EPSConsensus> = consolidated
+        .into_iter()
+        .map(|(mut eps, total, total_weight)| {
+            eps.consensus = PerShare(money_from_f64(
+                eps.consensus.0.currency,
+                total / total_weight,
+            ));
+            eps
+        })
+        .collect();
+    consolidated.sort_by_key(|eps| eps.fiscal_end_date);
+    Ok(consolidated)
+}
+
+/// Return the date and kind of the nearest upcoming catalyst for a symbol, the
+/// earliest of the `next_report_date`s of the EPS consensus in `data` and the
+/// `ex_date`s of `dividends` that is on or after `today`. Where an earnings
+/// report and an ex-dividend date fall on the same day the earnings report is
+/// returned. Returns `None` if there is no such date.
+pub fn next_catalyst(
+    data: &AnalystData,
+    dividends: &[DividendEstimate],
+    today: Date,
+) -> Option<(Date, CatalystKind)> {
+    data.eps
+        .iter()
+        .map(|eps| (eps.next_report_date, CatalystKind::Earnings))
+        .chain(
+            dividends
+                .iter()
+                .map(|dividend| (dividend.ex_date, CatalystKind::ExDividend)),
+        )
+        .filter(|(date, _)| *date >= today)
+        .min_by_key(|(date, _)| *date)
+}
+
+/// Calculate the dividend coverage ratio, annual earnings over annual dividends,
+/// where both the EPS consensus and the dividend are annualized according to
+/// their fiscal periods, see `FinancialPeriod::periods_per_year`. A coverage
+/// below `1.0` indicates that earnings do not cover the dividend. Returns
+/// `None` if the dividend is zero or the values are in different currencies.
+pub fn dividend_coverage(eps: &EPSConsensus, dividend: &DividendEstimate) -> Option<f64> {
+    if dividend.amount.0.currency != eps.consensus.0.currency {
+        None
+    } else {
+        let earnings = eps.consensus.0.as_f64() * f64::from(eps.fiscal_period.periods_per_year());
+        let paid =
+            dividend.amount.0.as_f64() * f64::from(dividend.fiscal_period.periods_per_year());
+        safe_div(earnings, paid)
+    }
+}
+
+/// Calculate the constant annual growth in earnings implied by `price` under a
+/// Gordon growth model with a `required_return`, such as `0.08` for 8%.
+///
+/// The model values the stock as a perpetuity of earnings growing at `g`,
+/// `price = E * (1 + g) / (r - g)`, where `E` is the consensus EPS annualized
+/// according to its fiscal period, see `FinancialPeriod::periods_per_year`.
+/// Solving for growth gives `g = (price * r - E) / (price + E)`. This assumes
+/// that all earnings are paid out, that growth continues at a constant rate
+/// forever, and that the required return is constant.
+///
+/// Returns `None` if the EPS or price is not positive, they are in different
+/// currencies, or the implied growth would not be below the required return.
+pub fn implied_growth(eps: &EPSConsensus, price: PerShare, required_return: f64) -> Option<f64> {
+    if eps.consensus.0.currency != price.0.currency
+        || eps.consensus.0.minor_amount() <= 0
+        || price.0.minor_amount() <= 0
+    {
+        return None;
+    }
+    let earnings = annualized_eps(eps);
+    let price = price.0.as_f64();
+    safe_div(price * required_return - earnings, price + earnings)
+        .filter(|growth| *growth < required_return)
+}
+
+/// Calculate the price/earnings to growth (PEG) ratio, where `eps_growth_pct`
+/// is expressed as a whole-number percentage (`20.0` for 20%). Returns `None`
+/// for a non-positive P/E or non-positive growth.
+pub fn peg_ratio(forward_pe: f64, eps_growth_pct: f64) -> Option<f64> {
+    if forward_pe > 0.0 && eps_growth_pct > 0.0 {
+        safe_div(forward_pe, eps_growth_pct)
+    } else {
+        None
+    }
+}
+
+/// Calculate the PEG ratio from a consensus series, using the forward P/E of
+/// the earliest fiscal period and the `eps_growth` of the series. Returns
+/// `None` in the cases that `forward_pe` or `peg_ratio` do.
+pub fn peg_from_consensus(series: &[EPSConsensus], price: PerShare) -> Option<f64> {
+    let nearest = series.iter().min_by_key(|eps| eps.fiscal_end_date)?;
+    peg_ratio(
+        forward_pe(price, nearest)?,
+        eps_growth(series).ok()?? * 100.0,
+    )
+}
+
+/// Calculate a single score in the range `0.0..=1.0`, where higher is more
+/// favorable, from the analyst data for a symbol and its `current` price. Each
+/// available component is first scaled to `0.0..=1.0`:
+///
+/// * rating; the latest `scaled_average` mapped so that `1.0` (buy) scores `1.0`
+///   and `5.0` (sell) scores `0.0`,
+/// * target upside; `upside(current)` clamped to `-1.0..=1.0` and mapped so that
+///   `-1.0` scores `0.0` and `1.0` scores `1.0`,
+/// * EPS growth; `eps_growth` clamped and mapped in the same way as upside.
+///
+/// The weighted average is taken of only the available components, returning
+/// `None` if none are available or their weights sum to zero.
+pub fn composite_score(
+    data: &AnalystData,
+    current: PerShare,
+    weights: &ScoreWeights,
+) -> Option<f64> {
+    composite_score_with(data, current, weights, &LinearScorer)
+}
+
+/// Calculate a score as `composite_score`, with the rating component scored
+/// by `scorer` rather than `LinearScorer`.
+pub fn composite_score_with(
+    data: &AnalystData,
+    current: PerShare,
+    weights: &ScoreWeights,
+    scorer: &impl RatingScorer,
+) -> Option<f64> {
+    let unit = |value: f64| (value.clamp(-1.0, 1.0) + 1.0) / 2.0;
+    let components = [
+        (
+            latest_ratings(&data.ratings).and_then(|r| scorer.score(r)),
+            weights.rating,
+        ),
+        (
+            data.target
+                .as_ref()
+                .and_then(|t| t.data.upside(current))
+                .map(unit),
+            weights.target_upside,
+        ),
+        (
+            eps_growth(&data.eps).ok().and_then(|g| g.map(unit)),
+            weights.eps_growth,
+        ),
+    ];
+    let (total, weight) = components
+        .iter()
+        .filter_map(|(score, weight)| score.map(|score| (score * weight, *weight)))
+        .fold((0.0, 0.0), |(t, w), (score, weight)| {
+            (t + score, w + weight)
+        });
+    if weight > 0.0 {
+        safe_div(total, weight)
+    } else {
+        None
+    }
+}
+
+/// Select the `n` highest scoring symbols, in descending order of score, from
+/// `scored`; equal scores are ordered by symbol. Selection is partial, only the
+/// selected values are sorted. Scores that cannot be compared, such as `NaN`,
+/// are ordered after all others, so are only selected if there are fewer than
+/// `n` other scores.
+pub fn top_n<T: PartialOrd + Clone>(scored: &[(Symbol, T)], n: usize) -> Vec<(Symbol, T)> {
+    select_n(scored, n, |a, b| {
+        compare_scores(&a.1, &b.1, true).then_with(|| a.0.cmp(&b.0))
+    })
+}
+
+/// Select the `n` lowest scoring symbols, in ascending order of score, from
+/// `scored`; as with `top_n` equal scores are ordered by symbol, and scores
+/// that cannot be compared, such as `NaN`, are ordered after all others.
+pub fn bottom_n<T: PartialOrd + Clone>(scored: &[(Symbol, T)], n: usize) -> Vec<(Symbol, T)> {
+    select_n(scored, n, |a, b| {
+        compare_scores(&a.1, &b.1, false).then_with(|| a.0.cmp(&b.0))
+    })
+}
+
+/// Compare the headline analyst data from two providers for `symbol`. Where a
+/// provider returns no data, or `RequestError::Unsupported`, for a request the
+/// corresponding field of the report is `None`; any other error is returned.
+/// Prices in different currencies are also not compared.
+pub fn reconcile(
+    a: &impl AnalystRecommendations,
+    b: &impl AnalystRecommendations,
+    symbol: Symbol,
+) -> RequestResult<ReconciliationReport> {
+    let target_delta = match (
+        unsupported_as_none(a.target_price(symbol.clone()))?,
+        unsupported_as_none(b.target_price(symbol.clone()))?,
+    ) {
+        (Some(a), Some(b)) => money_delta(a.data.average.0, b.data.average.0).map(PerShare),
+        _ => None,
+    };
+
+    let latest_average = |ratings: Option<Vec<Bounded<Ratings>>>| {
+        latest_ratings(&ratings.unwrap_or_default()).map(|r| r.scaled_average())
+    };
+    let rating_delta = match (
+        latest_average(unsupported_as_none(a.consensus_rating(symbol.clone()))?),
+        latest_average(unsupported_as_none(b.consensus_rating(symbol.clone()))?),
+    ) {
+        (Some(a), Some(b)) => Some(a - b),
+        _ => None,
+    };
+
+    let a_eps = unsupported_as_none(a.consensus_eps(symbol.clone()))?.unwrap_or_default();
+    let b_eps = unsupported_as_none(b.consensus_eps(symbol))?.unwrap_or_default();
+    let eps_delta = a_eps
+        .iter()
+        .min_by_key(|eps| eps.fiscal_end_date)
+        .and_then(|nearest| {
+            b_eps
+                .iter()
+                .find(|eps| eps.fiscal_period == nearest.fiscal_period)
+                .and_then(|other| money_delta(nearest.consensus.0, other.consensus.0))
+                .map(PerShare)
+        });
+
+    Ok(ReconciliationReport {
+        target_delta,
+        rating_delta,
+        eps_delta,
+    })
+}
+
+/// Blend a set of price targets into a single target, weighting each by its
+/// `number_of_analysts`; the total number of analysts saturates at the largest
+/// `Counter`. Returns `None` if there are no targets, no analysts, or if the
+/// targets are not all in the same currency (see `blend_in`).
+pub fn blend(targets: &[PriceTarget]) -> Option<PriceTarget> {
+    let currency = targets.first()?.average.0.currency;
+    if targets.iter().any(|t| {
+        t.high.0.currency != currency
+            || t.low.0.currency != currency
+            || t.average.0.currency != currency
+    }) {
+        return None;
+    }
+    let number_of_analysts: u64 = targets
+        .iter()
+        .map(|t| u64::from(t.number_of_analysts))
+        .sum();
+    if number_of_analysts == 0 {
+        return None;
+    }
+    let weighted = |price: fn(&PriceTarget) -> PerShare| {
+        let total: f64 = targets
+            .iter()
+            .map(|t| price(t).0.as_f64() * f64::from(t.number_of_analysts))
+            .sum();
+        try_money_from_f64(currency, total / number_of_analysts as f64).map(PerShare)
+    };
+    Some(PriceTarget {
+        high: weighted(|t| t.high)?,
+        low: weighted(|t| t.low)?,
+        average: weighted(|t| t.average)?,
+        number_of_analysts: Counter::try_from(number_of_analysts).unwrap_or(Counter::MAX),
+    })
+}
+
+/// Blend a set of price targets, as `blend`, after first converting each of
+/// them to the currency `to` using the rates in effect `on` the given date.
+pub fn blend_in(
+    targets: &[PriceTarget],
+    to: Currency,
+    rates: &impl ExchangeRates,
+    on: Date,
+) -> RequestResult<Option<PriceTarget>> {
+    let converted: RequestResult<Vec<PriceTarget>> =
+        targets.iter().map(|t| t.convert(to, rates, on)).collect();
+    Ok(blend(&converted?))
+}
+
+/// Blend a set of price targets, such as one from each of a number of sources,
+/// weighting each by an accuracy score for its source, such as the `hit_rate`
+/// of an `AccuracyReport` from the source's `AccuracyTracker`. The weights are
+/// normalized, each divided by their total, so only their relative size
+/// matters; negative and non-finite weights are treated as zero. The result
+/// has the accuracy-weighted high, low, and average, and the sum of the
+/// `number_of_analysts` of the targets with a positive weight, saturating at
+/// the largest `Counter`.
+///
+/// Returns `None` if the total weight is zero or the targets are not all in the
+/// same currency.
+pub fn accuracy_weighted_target(entries: &[(PriceTarget, f64)]) -> Option<PriceTarget> {
+    let currency = entries.first()?.0.average.0.currency;
+    if entries.iter().any(|(t, _)| {
+        t.high.0.currency != currency
+            || t.low.0.currency != currency
+            || t.average.0.currency != currency
+    }) {
+        return None;
+    }
+    let weighted: Vec<(&PriceTarget, f64)> = entries
+        .iter()
+        .filter(|(_, weight)| weight.is_finite() && *weight > 0.0)
+        .map(|(target, weight)| (target, *weight))
+        .collect();
+    let total: f64 = weighted.iter().map(|(_, weight)| weight).sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let blended = |price: fn(&PriceTarget) -> PerShare| {
+        let value: f64 = weighted
+            .iter()
+            .map(|(t, weight)| price(t).0.as_f64() * weight / total)
+            .sum();
+        try_money_from_f64(currency, value).map(PerShare)
+    };
+    Some(PriceTarget {
+        high: blended(|t| t.high)?,
+        low: blended(|t| t.low)?,
+        average: blended(|t| t.average)?,
+        number_of_analysts: Counter::try_from(
+            weighted
+                .iter()
+                .map(|(t, _)| u64::from(t.number_of_analysts))
+                .sum::<u64>(),
+        )
+        .unwrap_or(Counter::MAX),
+    })
+}
+
+/// Blend a set of price targets, as `blend`, returning the blended average with
+/// an uncertainty band as `(low_band, central, high_band)`. The band is one
+/// standard deviation either side of the central value, where the deviation is
+/// of the inputs' `average` targets weighted by their `number_of_analysts`; so a
+/// single target has a zero-width band. The low band is never negative.
+///
+/// Returns `None` in the same cases as `blend`.
+pub fn blend_with_band(targets: &[PriceTarget]) -> Option<(PerShare, PerShare, PerShare)> {
+    let central = blend(targets)?.average;
+    let mean = central.0.as_f64();
+    let variance = safe_div(
+        targets
+            .iter()
+            .map(|t| f64::from(t.number_of_analysts) * (t.average.0.as_f64() - mean).powi(2))
+            .sum(),
+        targets
+            .iter()
+            .map(|t| u64::from(t.number_of_analysts))
+            .sum::<u64>() as f64,
+    )?;
+    let deviation = variance.sqrt();
+    Some((
+        PerShare(try_money_from_f64(
+            central.0.currency,
+            (mean - deviation).max(0.0),
+        )?),
+        central,
+        PerShare(try_money_from_f64(central.0.currency, mean + deviation)?),
+    ))
+}
+
+/// Align a bounded series to a set of dates, returning for each of `dates` the
+/// data of the value in `series` whose range, inclusive of the start and end
+/// dates, contains it, or `None` if no value covers the date. Where ranges
+/// overlap the value with the later start date is used, and where they also
+/// start together the later value in `series`.
+pub fn align_to_dates<T: Clone>(series: &[Bounded<T>], dates: &[Date]) -> Vec<Option<T>> {
+    dates
+        .iter()
+        .map(|date| {
+            series
+                .iter()
+                .filter(|b| b.start_date.date() <= *date && *date <= b.end_date.date())
+                .max_by_key(|b| b.start_date)
+                .map(|b| b.data.clone())
+        })
+        .collect()
+}
+
+/// Validate all of `items`, returning a summary of the number that are valid
+/// and the number that failed with each error; an item with more than one
+/// error is counted once against each.
+pub fn validate_batch<T: Validatable>(items: &[T]) -> ValidationSummary {
+    let mut summary = ValidationSummary {
+        total: items.len(),
+        ..Default::default()
+    };
+    for item in items {
+        match item.validate_all() {
+            Ok(()) => summary.valid += 1,
+            Err(errors) => {
+                for error in errors {
+                    *summary.by_error.entry(error).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+    summary
+}
+
+/// Detect changes in the consensus rating between consecutive periods in
+/// `history`, ordered by start date, where the `scaled_average` changed by more
+/// than `min_magnitude`. As lower averages are more positive, a fall in the
+/// average is an upgrade. Periods with no ratings are skipped.
+pub fn detect_rating_changes(
+    history: &[Bounded<Ratings>],
+    min_magnitude: f64,
+) -> Vec<RatingChangeEvent> {
+    let mut periods: Vec<&Bounded<Ratings>> =
+        history.iter().filter(|r| !r.data.is_empty()).collect();
+    periods.sort_by_key(|r| r.start_date);
+    periods
+        .windows(2)
+        .filter_map(|pair| {
+            rating_change(
+                &pair[0].data,
+                &pair[1].data,
+                pair[1].start_date.date(),
+                min_magnitude,
+            )
+        })
+        .collect()
+}
+
+/// Smooth the consensus in `history` with a trailing moving average of the
+/// `scaled_average` over `window` periods, ordered by end date. Each point is
+/// dated at the end of the last period in its window and only full windows are
+/// returned, so a `window` of `1` is the raw series. If `window` is longer than
+/// the history a single point averages all periods. Periods with no ratings are
+/// skipped, and a `window` of `0` returns no points.
+pub fn smoothed_consensus(history: &[Bounded<Ratings>], window: usize) -> Vec<(Date, f64)> {
+    let mut periods: Vec<&Bounded<Ratings>> =
+        history.iter().filter(|r| !r.data.is_empty()).collect();
+    if window == 0 || periods.is_empty() {
+        return Vec::new();
+    }
+    periods.sort_by_key(|r| r.end_date);
+    let window = window.min(periods.len());
+    periods
+        .windows(window)
+        .map(|w| {
+            let total: f64 = w.iter().map(|r| r.data.scaled_average()).sum();
+            (w[window - 1].end_date.date(), total / window as f64)
+        })
+        .collect()
+}
+
+/// Score the stability of the consensus in `history` over the trailing `window`
+/// days to `now`, in the range `0.0..=1.0`, where `1.0` is an unchanged
+/// consensus. This is `1.0 - variance / 4.0`, where `variance` is the variance
+/// of the `scaled_average` of the periods that overlap the window; `4.0` being
+/// the largest variance possible on the rating scale. Periods with no ratings
+/// are skipped, and `None` is returned if fewer than two periods qualify.
+pub fn rating_stability(history: &[Bounded<Ratings>], window: i64, now: Date) -> Option<f64> {
+    let from = now.checked_sub_signed(chrono::Duration::days(window.max(0)))?;
+    let averages: Vec<f64> = history
+        .iter()
+        .filter(|r| !r.data.is_empty())
+        .filter(|r| r.start_date.date() <= now && from <= r.end_date.date())
+        .map(|r| r.data.scaled_average())
+        .collect();
+    if averages.len() < 2 {
+        return None;
+    }
+    let n = averages.len() as f64;
+    let mean = averages.iter().sum::<f64>() / n;
+    let variance = averages.iter().map(|a| (a - mean).powi(2)).sum::<f64>() / n;
+    Some((1.0 - variance / 4.0).clamp(0.0, 1.0))
+}
+
+/// Calculate a conviction index, in the range `0.0..=1.0`, measuring how closely
+/// analysts agree regardless of the direction of the consensus. This is
+/// `(1.0 - H / ln(5)) * n / (n + 1)` where `H` is the Shannon entropy of the
+/// distribution of the `n` ratings across the five rating types. So unanimous
+/// ratings approach `1.0` as the count grows and an even split across all
+/// types is `0.0`. Returns `None` if there are no ratings.
+pub fn conviction_index(ratings: &Ratings) -> Option<f64> {
+    let count: f64 = ratings.ratings.values().map(|c| f64::from(*c)).sum();
+    if count == 0.0 {
+        return None;
+    }
+    let entropy: f64 = ratings
+        .ratings
+        .values()
+        .filter(|c| **c > 0)
+        .map(|c| {
+            let p = f64::from(*c) / count;
+            -p * p.ln()
+        })
+        .sum();
+    let dispersion = entropy / 5f64.ln();
+    Some(((1.0 - dispersion) * count / (count + 1.0)).clamp(0.0, 1.0))
+}
+
+/// Combine the rating consensus and the upside implied by `target` from
+/// `current` into a single verdict. The ratings are classified with the default
+/// `ConsensusThresholds`, and an upside of at least 20% (or a downside of at
+/// least 20%) is large:
+///
+/// * bullish ratings give `StrongBuy` with a large upside, `Buy` with any
+///   other non-negative upside, and otherwise `Hold`.
+/// * bearish ratings give `StrongSell` with a large downside, `Sell` with any
+///   other non-positive upside, and otherwise `Hold`.
+/// * neutral ratings give `Buy` with a large upside, `Sell` with a large
+///   downside, and otherwise `Hold`.
+///
+/// Where there are no ratings the verdict is from the upside alone; `StrongBuy`
+/// or `StrongSell` for a large move, `Buy` or `Sell` for a move of at least 5%,
+/// and otherwise `Hold`. If the upside cannot be calculated it is taken as zero.
+pub fn verdict(ratings: Option<&Ratings>, target: &PriceTarget, current: PerShare) -> Verdict {
+    let upside = target.upside(current).unwrap_or(0.0);
+    match ratings.and_then(|r| r.classify(&ConsensusThresholds::default())) {
+        Some(Sentiment::Bullish) if upside >= VERDICT_LARGE_MOVE => Verdict::StrongBuy,
+        Some(Sentiment::Bullish) if upside >= 0.0 => Verdict::Buy,
+        Some(Sentiment::Bearish) if upside <= -VERDICT_LARGE_MOVE => Verdict::StrongSell,
+        Some(Sentiment::Bearish) if upside <= 0.0 => Verdict::Sell,
+        Some(Sentiment::Neutral) if upside >= VERDICT_LARGE_MOVE => Verdict::Buy,
+        Some(Sentiment::Neutral) if upside <= -VERDICT_LARGE_MOVE => Verdict::Sell,
+        Some(_) => Verdict::Hold,
+        None if upside >= VERDICT_LARGE_MOVE => Verdict::StrongBuy,
+        None if upside >= VERDICT_MOVE => Verdict::Buy,
+        None if upside <= -VERDICT_LARGE_MOVE => Verdict::StrongSell,
+        None if upside <= -VERDICT_MOVE => Verdict::Sell,
+        None => Verdict::Hold,
+    }
+}
+
+/// Score, in the range `0.0..=1.0`, how well the direction of the rating
+/// consensus agrees with the direction and magnitude implied by the price
+/// target, where `1.0` is fully consistent; such as for flagging a sell-rated
+/// stock with a target implying a large upside.
+///
+/// The ratings are expressed as a signal in `-1.0..=1.0`, `2p - 1` where `p`
+/// is `Ratings::outperform_probability`, and the target as its `upside` from
+/// `current` scaled so that a 25% move is `1.0`, clamped to `-1.0..=1.0`. The
+/// score is `1.0 - |ratings - target| / 2`. At the hold midpoint the ratings
+/// signal is `0.0`, so a neutral consensus scores `1.0` with a flat target and
+/// never less than `0.5`, however large the implied move.
+///
+/// Returns `1.0` if there are no ratings, or the upside cannot be calculated,
+/// as there is nothing to be inconsistent.
+pub fn consistency_score(ratings: &Ratings, target: &PriceTarget, current: PerShare) -> f64 {
+    match (ratings.outperform_probability(), target.upside(current)) {
+        (Some(probability), Some(upside)) => {
+            let rating_signal = 2.0 * probability - 1.0;
+            let target_signal = (upside / FULL_CONVICTION_UPSIDE).clamp(-1.0, 1.0);
+            1.0 - (rating_signal - target_signal).abs() / 2.0
+        }
+        _ => 1.0,
+    }
+}
+
+/// Return the ratings in effect on `now` from an already fetched `history`;
+/// that is, of the period whose start and end dates include `now`. Where
+/// periods overlap the latest starting is used, and `None` is returned if no
+/// period includes `now`.
+pub fn current_ratings(history: &[Bounded<Ratings>], now: Date) -> Option<&Ratings> {
+    history
+        .iter()
+        .filter(|r| r.start_date.date() <= now && now <= r.end_date.date())
+        .max_by_key(|r| r.start_date)
+        .map(|r| &r.data)
+}
+
+/// Return the freshness of a ratings `history` as `(days_since_newest,
+/// days_since_oldest)`, the number of days from the latest and earliest end
+/// dates of its periods to `now`. A period that has not yet ended counts as
+/// zero days old. Returns `None` if `history` is empty.
+pub fn rating_freshness(history: &[Bounded<Ratings>], now: Date) -> Option<(i64, i64)> {
+    let newest = history.iter().map(|r| r.end_date.date()).max()?;
+    let oldest = history.iter().map(|r| r.end_date.date()).min()?;
+    let age = |date: Date| (now - date).num_days().max(0);
+    Some((age(newest), age(oldest)))
+}
+
+/// Return the count of `rating` in each period of `history`, by the start date
+/// of the period, in date order; periods without the rating type count as zero.
+pub fn rating_type_trend(history: &[Bounded<Ratings>], rating: RatingType) -> Vec<(Date, Counter)> {
+    let mut trend: Vec<(Date, Counter)> = history
+        .iter()
+        .map(|r| {
+            (
+                r.start_date.date(),
+                r.data.ratings.get(&rating).copied().unwrap_or(0),
+            )
+        })
+        .collect();
+    trend.sort_by_key(|(date, _)| *date);
+    trend
+}
+
+/// Count `targets`, such as the individual targets of `AnalystEstimate`s, into
+/// buckets of width `bucket_size`, returning the lower bound and count of each
+/// bucket. Bucket bounds are multiples of `bucket_size` and the buckets span
+/// from the lowest to the highest target, including empty buckets between
+/// them as zero counts.
+///
+/// Returns `None` if `bucket_size` is not positive, the values are not all in
+/// the same currency, the range of targets would need more than
+/// `MAX_HISTOGRAM_BUCKETS` buckets, or a bucket bound is too large to
+/// represent.
+pub fn target_histogram(targets: &[Money], bucket_size: Money) -> Option<Vec<(Money, usize)>> {
+    let size = i64::from(bucket_size.minor_amount());
+    if size <= 0 || targets.iter().any(|t| t.currency != bucket_size.currency) {
+        return None;
+    }
+    let bucket = |t: &Money| i64::from(t.minor_amount()).div_euclid(size);
+    let (first, last) = match (
+        targets.iter().map(bucket).min(),
+        targets.iter().map(bucket).max(),
+    ) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Some(Vec::new()),
+    };
+    let buckets = usize::try_from(last - first + 1).ok()?;
+    if buckets > MAX_HISTOGRAM_BUCKETS {
+        return None;
+    }
+    let mut counts = vec![0usize; buckets];
+    for target in targets {
+        counts[(bucket(target) - first) as usize] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| {
+            let lower = i32::try_from((first + i as i64) * size).ok()?;
+            Some((Money::of_minor(bucket_size.currency, lower), count))
+        })
+        .collect()
+}
+
+/// Measure the round-number bias of `targets`, such as the individual targets
+/// of `AnalystEstimate`s, as the fraction of them, in the range `0.0..=1.0`,
+/// that are an exact multiple of `increment`. A high value suggests targets
+/// anchored on round numbers rather than derived from a valuation. Targets in
+/// a different currency to `increment` are never counted as round; returns
+/// `0.0` if there are no targets or `increment` is not positive.
+pub fn round_number_bias(targets: &[Money], increment: Money) -> f64 {
+    let size = increment.minor_amount();
+    if size <= 0 {
+        return 0.0;
+    }
+    let round = targets
+        .iter()
+        .filter(|t| t.currency == increment.currency && t.minor_amount().rem_euclid(size) == 0)
+        .count();
+    safe_div(round as f64, targets.len() as f64).unwrap_or(0.0)
+}
+
+/// Calculate the scaled average, as `Ratings::scaled_average`, of the ratings
+/// in `estimates`, with each estimate weighted by the weight for its firm in
+/// `tier_weight`; firms not present have a weight of `1.0`. Estimates with no
+/// rating are skipped, and `None` is returned if the total weight is zero.
+pub fn scaled_average_by_firm(
+    estimates: &[AnalystEstimate],
+    tier_weight: &HashMap<String, f64>,
+) -> Option<f64> {
+    let (total, weight) = estimates
+        .iter()
+        .filter_map(|estimate| {
+            estimate.rating.map(|rating| {
+                let weight = tier_weight.get(&estimate.firm).copied().unwrap_or(1.0);
+                (
+                    f64::from(rating.to_code(CodeScale::OneIsBuy)) * weight,
+                    weight,
+                )
+            })
+        })
+        .fold((0.0, 0.0), |(t, w), (score, weight)| {
+            (t + score, w + weight)
+        });
+    if weight > 0.0 {
+        safe_div(total, weight)
+    } else {
+        None
+    }
+}
+
+/// Adjust a previously retrieved `target` for each of the `actions`, applying
+/// `PriceTarget::split_adjust` in date order. Only splits after the target was
+/// retrieved should be included.
+pub fn adjust_target_for_actions(target: &PriceTarget, actions: &[Split]) -> PriceTarget {
+    let mut splits: Vec<&Split> = actions.iter().collect();
+    splits.sort_by_key(|split| split.date);
+    splits.into_iter().fold(target.clone(), |target, split| {
+        target.split_adjust(split.ratio)
+    })
+}
+
+/// Calibrate provider `b`'s rating scale onto provider `a`'s, from samples of
+/// each provider's `Ratings::scaled_average` by symbol. The symbols present in
+/// both sets of samples are paired, using the first sample for a symbol, and
+/// the mapping `a = slope * b + intercept` is fitted by ordinary least squares.
+/// Returns `None` if fewer than two symbols are present in both, or all of the
+/// paired `b` samples are equal.
+pub fn calibrate_scales(
+    a_samples: &[(Symbol, f64)],
+    b_samples: &[(Symbol, f64)],
+) -> Option<ScaleMapping> {
+    let mut b_by_symbol: HashMap<&Symbol, f64> = HashMap::new();
+    for (symbol, value) in b_samples.iter().rev() {
+        b_by_symbol.insert(symbol, *value);
+    }
+    let mut paired: Vec<(f64, f64)> = Vec::new();
+    for (i, (symbol, a)) in a_samples.iter().enumerate() {
+        if a_samples[..i].iter().any(|(earlier, _)| earlier == symbol) {
+            continue;
+        }
+        if let Some(b) = b_by_symbol.get(symbol) {
+            paired.push((*b, *a));
+        }
+    }
+    let (slope, intercept) = linear_regression(&paired)?;
+    Some(ScaleMapping { slope, intercept })
+}
+
+/// Fit a line to dated `points` by ordinary least squares, returning the
+/// `(slope, intercept)` where the slope is the change in value per day and the
+/// intercept is the value on the date of the first point. Returns `None` if
+/// there are fewer than two points or they are all on the same date.
+pub fn linreg_dated(points: &[(Date, f64)]) -> Option<(f64, f64)> {
+    let origin = points.first()?.0;
+    let offsets: Vec<(f64, f64)> = points
+        .iter()
+        .map(|(date, value)| ((*date - origin).num_days() as f64, *value))
+        .collect();
+    linear_regression(&offsets)
+}
+
+/// Project a price target `horizon_days` beyond the latest snapshot in `history`,
+/// assuming the trend in revisions continues. A least-squares linear trend is
+/// fitted to the `average` target over time and extrapolated, the `high` and
+/// `low` of the latest target are then scaled in proportion to the change in
+/// the average. The projected average is never negative, so the result always
+/// satisfies `PriceTarget::validate` if the latest target did.
+///
+/// Returns `None` if there are fewer than two snapshots, they are all on the
+/// same date, they are in different currencies, the latest average is zero, or
+/// the projected prices are too large to represent.
+pub fn projected_target(
+    history: &[Snapshot<PriceTarget>],
+    horizon_days: i64,
+) -> Option<PriceTarget> {
+    let latest = history.iter().max_by_key(|s| s.date)?;
+    let currency = latest.data.average.0.currency;
+    if history.len() < 2
+        || latest.data.average.0.minor_amount() == 0
+        || history
+            .iter()
+            .any(|s| s.data.average.0.currency != currency)
+    {
+        return None;
+    }
+    let origin = history.iter().map(|s| s.date).min()?;
+    let points: Vec<(f64, f64)> = history
+        .iter()
+        .map(|s| {
+            (
+                (s.date - origin).num_seconds() as f64 / 86_400.0,
+                s.data.average.0.as_f64(),
+            )
+        })
+        .collect();
+    let (slope, intercept) = linear_regression(&points)?;
+    let x = (latest.date - origin).num_seconds() as f64 / 86_400.0 + horizon_days as f64;
+    let projected = (intercept + slope * x).max(0.0);
+    let scale = safe_div(projected, latest.data.average.0.as_f64())?;
+    let scaled =
+        |value: PerShare| try_money_from_f64(currency, value.0.as_f64() * scale).map(PerShare);
+    Some(PriceTarget {
+        high: scaled(latest.data.high)?,
+        low: scaled(latest.data.low)?,
+        average: PerShare(try_money_from_f64(currency, projected)?),
+        number_of_analysts: latest.data.number_of_analysts,
+    })
+}
+
+/// Reconstruct a daily series of price targets from irregular `snapshots`,
+/// returning for each day from `from` to `to`, inclusive, the target of the
+/// latest snapshot dated on or before that day. Days before the first snapshot
+/// are omitted, and where snapshots share a date the later in `snapshots` is
+/// used. The snapshots need not be sorted.
+pub fn forward_fill_targets(
+    snapshots: &[Snapshot<PriceTarget>],
+    from: Date,
+    to: Date,
+) -> Vec<(Date, PriceTarget)> {
+    let mut sorted: Vec<&Snapshot<PriceTarget>> = snapshots.iter().collect();
+    sorted.sort_by_key(|s| s.date.date());
+    let mut series = Vec::new();
+    let mut next = 0;
+    let mut current: Option<&PriceTarget> = None;
+    let mut day = Some(from);
+    while let Some(date) = day.filter(|date| *date <= to) {
+        while next < sorted.len() && sorted[next].date.date() <= date {
+            current = Some(&sorted[next].data);
+            next += 1;
+        }
+        if let Some(target) = current {
+            series.push((date, target.clone()));
+        }
+        day = date.succ_opt();
+    }
+    series
+}
+
+/// Express the forward P/E of `symbol` as a z-score relative to its peers,
+/// `(pe - mean) / stdev`, where the mean and standard deviation are weighted
+/// by the peer scores from `Peers::scored_peers`. Forward P/E uses the EPS
+/// consensus with the nearest fiscal end date and the price in `price_lookup`.
+/// Negative values indicate that the symbol is relatively cheap.
+///
+/// Returns `Ok(None)` if the symbol has no forward P/E, if fewer than two
+/// peers have one, or if the peer P/E values do not vary.
+pub fn peer_relative_zscore(
+    prov_peers: &impl Peers,
+    prov_reco: &impl AnalystRecommendations,
+    symbol: Symbol,
+    price_lookup: &HashMap<Symbol, PerShare>,
+) -> RequestResult<Option<f64>> {
+    let pe = match nearest_forward_pe(prov_reco, &symbol, price_lookup)? {
+        None => return Ok(None),
+        Some(pe) => pe,
+    };
+    let mut peers: Vec<(f64, f64)> = Vec::new();
+    for (peer, score) in prov_peers.scored_peers(symbol.clone())? {
+        if peer != symbol && score > 0.0 {
+            if let Some(peer_pe) = nearest_forward_pe(prov_reco, &peer, price_lookup)? {
+                peers.push((peer_pe, score));
+            }
+        }
+    }
+    if peers.len() < 2 {
+        return Ok(None);
+    }
+    let total: f64 = peers.iter().map(|(_, w)| w).sum();
+    let mean = match safe_div(peers.iter().map(|(pe, w)| pe * w).sum(), total) {
+        None => return Ok(None),
+        Some(mean) => mean,
+    };
+    let variance = safe_div(
+        peers.iter().map(|(pe, w)| w * (pe - mean).powi(2)).sum(),
+        total,
+    );
+    Ok(variance.and_then(|variance| safe_div(pe - mean, variance.sqrt())))
+}
+
+/// Measure how similar the consensus rating of `symbol` is to that of its
+/// peers, in the range `0.0..=1.0`, where `1.0` is identical to the peer group
+/// and `0.0` is as far from it as the rating scale allows. This is
+/// `1.0 - |average - mean| / 4.0`, where `average` is the `scaled_average` of
+/// the latest ratings of `symbol` and `mean` is the mean of the peers' latest
+/// averages, weighted by the peer scores from `Peers::scored_peers`. Peers
+/// that are unknown to, or unsupported by, `prov_reco` are skipped.
+///
+/// Returns `Ok(None)` if the symbol has no ratings, or if fewer than two peers
+/// have ratings.
+pub fn crowding_index(
+    prov_peers: &impl Peers,
+    prov_reco: &impl AnalystRecommendations,
+    symbol: Symbol,
+) -> RequestResult<Option<f64>> {
+    let average = match latest_scaled_average(prov_reco, &symbol)? {
+        None => return Ok(None),
+        Some(average) => average,
+    };
+    let mut peers: Vec<(f64, f64)> = Vec::new();
+    for (peer, score) in prov_peers.scored_peers(symbol.clone())? {
+        if peer != symbol && score > 0.0 {
+            if let Some(peer_average) = latest_scaled_average(prov_reco, &peer)? {
+                peers.push((peer_average, score));
+            }
+        }
+    }
+    if peers.len() < 2 {
+        return Ok(None);
+    }
+    let total: f64 = peers.iter().map(|(_, w)| w).sum();
+    let mean = safe_div(peers.iter().map(|(a, w)| a * w).sum(), total);
+    Ok(mean.map(|mean| (1.0 - (average - mean).abs() / 4.0).clamp(0.0, 1.0)))
+}
+
+/// Convert a ratings `history` to columns, a more compact form to serialize
+/// for long histories; the periods keep the order of `history`. The conversion
+/// is lossless, see `ratings_history_from_columns`.
+#[cfg(feature = "serde")]
+pub fn ratings_history_to_columns(history: &[Bounded<Ratings>]) -> RatingsColumns {
+    let count = |rating: RatingType| -> Vec<Option<Counter>> {
+        history
+            .iter()
+            .map(|r| r.data.ratings.get(&rating).copied())
+            .collect()
+    };
+    RatingsColumns {
+        start_dates: history.iter().map(|r| r.start_date).collect(),
+        end_dates: history.iter().map(|r| r.end_date).collect(),
+        attributions: history.iter().map(|r| r.attribution().cloned()).collect(),
+        scale_marks: history.iter().map(|r| r.data.scale_mark).collect(),
+        buy: count(RatingType::Buy),
+        outperform: count(RatingType::Outperform),
+        hold: count(RatingType::Hold),
+        underperform: count(RatingType::Underperform),
+        sell: count(RatingType::Sell),
+    }
+}
+
+/// Convert `columns` back to a ratings history, the inverse of
+/// `ratings_history_to_columns`. Returns `None` if the columns do not all
+/// have the same length.
+#[cfg(feature = "serde")]
+pub fn ratings_history_from_columns(columns: &RatingsColumns) -> Option<Vec<Bounded<Ratings>>> {
+    let len = columns.start_dates.len();
+    let counts = [
+        (RatingType::Buy, &columns.buy),
+        (RatingType::Outperform, &columns.outperform),
+        (RatingType::Hold, &columns.hold),
+        (RatingType::Underperform, &columns.underperform),
+        (RatingType::Sell, &columns.sell),
+    ];
+    if columns.end_dates.len() != len
+        || columns.attributions.len() != len
+        || columns.scale_marks.len() != len
+        || counts.iter().any(|(_, column)| column.len() != len)
+    {
+        return None;
+    }
+    Some(
+        (0..len)
+            .map(|i| {
+                let ratings = Bounded::new(
+                    columns.start_dates[i],
+                    columns.end_dates[i],
+                    Ratings {
+                        ratings: counts
+                            .iter()
+                            .filter_map(|(rating, column)| column[i].map(|count| (*rating, count)))
+                            .collect(),
+                        scale_mark: columns.scale_marks[i],
+                    },
+                );
+                match &columns.attributions[i] {
+                    Some(attribution) => ratings.with_attribution(attribution.clone()),
+                    None => ratings,
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Aggregate the current consensus ratings of `symbols` by sector, merging the
+/// latest ratings of each sector's members with `Ratings::merge`. Each symbol
+/// is classified by the `sector` of its `Security` from `prov_class`; symbols
+/// with no sector, or unknown to, or unsupported by, `prov_class` are grouped
+/// under `UNCLASSIFIED_SECTOR`. Symbols with no ratings, or unknown to, or
+/// unsupported by, `prov_reco` are skipped, so a sector is only present if at
+/// least one of its members has ratings.
+pub fn sector_consensus(
+    prov_class: &impl FetchCompanyInformation,
+    prov_reco: &impl AnalystRecommendations,
+    symbols: &Symbols,
+) -> RequestResult<HashMap<String, Ratings>> {
+    let mut sectors: HashMap<String, Ratings> = HashMap::new();
+    for symbol in symbols {
+        let history = absent_as_none(prov_reco.consensus_rating(symbol.clone()))?;
+        let ratings = match history.as_deref().and_then(latest_ratings) {
+            None => continue,
+            Some(ratings) => ratings,
+        };
+        let sector = absent_as_none(prov_class.about(symbol.clone()))?
+            .and_then(|about| about.security.sector)
+            .unwrap_or_else(|| UNCLASSIFIED_SECTOR.to_string());
+        let merged = match sectors.get(&sector) {
+            None => ratings.clone(),
+            Some(existing) => existing.merge(ratings),
+        };
+        sectors.insert(sector, merged);
+    }
+    Ok(sectors)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+const SCALE_MARK_TOLERANCE: f32 = 1e-4;
+
+const FULL_CONVICTION_UPSIDE: f64 = 0.25;
+
+const VERDICT_LARGE_MOVE: f64 = 0.2;
+
+const VERDICT_MOVE: f64 = 0.05;
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct AnalystDataDocument<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    data: &'a AnalystData,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct OwnedAnalystDataDocument {
+    schema_version: u32,
+    #[serde(flatten)]
+    data: AnalystData,
+}
+
+#[derive(Debug, Default)]
+struct WrappedState {
+    cache: HashMap<CacheKey, (Instant, CachedValue)>,
+    next_request: Option<Instant>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum CacheKey {
+    Target(Symbol),
+    Ratings(Symbol),
+    Eps(Symbol),
+    EpsAt(Symbol, Date),
+    Estimates(Symbol),
+}
+
+#[derive(Debug, Clone)]
+enum CachedValue {
+    Target(Snapshot<PriceTarget>),
+    Ratings(Vec<Bounded<Ratings>>),
+    Eps(Vec<EPSConsensus>),
+    EpsAt(Option<Vec<EPSConsensus>>),
+    Estimates(Option<Vec<AnalystEstimate>>),
+}
+
+/// A 64-bit FNV-1a hash, used for fingerprints as its output is fixed, unlike
+/// the randomly keyed `std::collections::hash_map::DefaultHasher`.
+struct Fingerprint(u64);
+
+impl Fingerprint {
+    fn new() -> Self {
+        Fingerprint(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_str(&mut self, s: &str) {
+        self.write(&(s.len() as u64).to_le_bytes());
+        self.write(s.as_bytes());
+    }
+
+    fn write_money(&mut self, money: Money) {
+        self.write_str(&money.currency.code());
+        self.write(&money.minor_amount().to_le_bytes());
+    }
+
+    fn write_option_money(&mut self, money: Option<Money>) {
+        match money {
+            None => self.write(&[0]),
+            Some(money) => {
+                self.write(&[1]);
+                self.write_money(money);
+            }
+        }
+    }
+
+    fn write_date(&mut self, date: Date) {
+        self.write(&date.num_days_from_ce().to_le_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn eps_content_fingerprint(eps: &EPSConsensus) -> u64 {
+    let mut fp = Fingerprint::new();
+    fp.write_str(&eps.fiscal_period.to_string());
+    fp.write_date(eps.fiscal_end_date);
+    fp.write_money(eps.consensus.0);
+    fp.finish()
+}
+
+fn as_validation_result(errors: Vec<ValidationError>) -> Result<(), Vec<ValidationError>> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn check_fiscal_calendars(series: &[EPSConsensus]) -> Result<(), AnalysisError> {
+    match series.first() {
+        Some(first)
+            if series
+                .iter()
+                .any(|eps| eps.fiscal_calendar != first.fiscal_calendar) =>
+        {
+            Err(AnalysisError::MixedFiscalCalendars)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn latest_ratings(history: &[Bounded<Ratings>]) -> Option<&Ratings> {
+    history
+        .iter()
+        .filter(|r| !r.data.is_empty())
+        .max_by_key(|r| r.start_date)
+        .map(|r| &r.data)
+}
+
+fn latest_scaled_average(
+    provider: &impl AnalystRecommendations,
+    symbol: &Symbol,
+) -> RequestResult<Option<f64>> {
+    let history = absent_as_none(provider.consensus_rating(symbol.clone()))?;
+    Ok(history
+        .as_deref()
+        .and_then(latest_ratings)
+        .map(Ratings::scaled_average))
+}
+
+fn annualized_eps(eps: &EPSConsensus) -> f64 {
+    eps.consensus.0.as_f64() * f64::from(eps.fiscal_period.periods_per_year())
+}
+
+fn nearest_forward_pe(
+    provider: &impl AnalystRecommendations,
+    symbol: &Symbol,
+    price_lookup: &HashMap<Symbol, PerShare>,
+) -> RequestResult<Option<f64>> {
+    let price = match price_lookup.get(symbol) {
+        None => return Ok(None),
+        Some(price) => *price,
+    };
+    let series = unsupported_as_none(provider.consensus_eps(symbol.clone()))?;
+    Ok(series
+        .as_ref()
+        .and_then(|series| series.iter().min_by_key(|eps| eps.fiscal_end_date))
+        .and_then(|eps| forward_pe(price, eps)))
+}
+
+fn linear_regression(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let sxy: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let slope = safe_div(sxy, sxx)?;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+fn rating_change(
+    from: &Ratings,
+    to: &Ratings,
+    date: Date,
+    min_magnitude: f64,
+) -> Option<RatingChangeEvent> {
+    let change = to.scaled_average() - from.scaled_average();
+    if change.abs() > min_magnitude {
+        Some(RatingChangeEvent {
+            date,
+            direction: if change < 0.0 {
+                Direction::Upgrade
+            } else {
+                Direction::Downgrade
+            },
+            magnitude: change.abs(),
+        })
+    } else {
+        None
+    }
+}
+
+fn select_n<T, F>(scored: &[(Symbol, T)], n: usize, compare: F) -> Vec<(Symbol, T)>
+where
+    T: Clone,
+    F: Fn(&(Symbol, T), &(Symbol, T)) -> Ordering,
+{
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut selected = scored.to_vec();
+    if n < selected.len() {
+        selected.select_nth_unstable_by(n - 1, &compare);
+        selected.truncate(n);
+    }
+    selected.sort_by(compare);
+    selected
+}
+
+/// Order scores ascending, or descending, with any score that cannot be compared
+/// to itself, such as `NaN`, last in either case.
+fn compare_scores<T: PartialOrd>(a: &T, b: &T, descending: bool) -> Ordering {
+    let comparable = |v: &T| v.partial_cmp(v).is_some();
+    match (comparable(a), comparable(b)) {
+        (true, true) => {
+            let ordering = a.partial_cmp(b).unwrap_or(Ordering::Equal);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        }
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => Ordering::Equal,
+    }
+}
+
+/// Treat a result that is not covered, `BadSymbolError`, or is `Unsupported`
+/// as absent data. This is not a coverage status, see `coverage_status_of`; it
+/// is for callers that only need to know whether there is data.
+fn absent_as_none<T>(result: RequestResult<T>) -> RequestResult<Option<T>> {
+    match result {
+        Err(RequestError::BadSymbolError(_)) => Ok(None),
+        result => unsupported_as_none(result),
+    }
+}
+
+fn unsupported_as_none<T>(result: RequestResult<T>) -> RequestResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(RequestError::Unsupported) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Return `primary` if it is `Ok` and `present`, otherwise the `secondary`
+/// result; unless both are absent and only the secondary failed.
+fn merge_results<T, S, P>(primary: RequestResult<T>, secondary: S, present: P) -> RequestResult<T>
+where
+    S: FnOnce() -> RequestResult<T>,
+    P: Fn(&T) -> bool,
+{
+    match primary {
+        Ok(value) if present(&value) => Ok(value),
+        primary => match secondary() {
+            Err(_) if primary.is_ok() => primary,
+            secondary => secondary,
+        },
+    }
+}
+
+/// Return `value`, with `source` as its source if it has none.
+fn snapshot_or_source<T>(value: Snapshot<T>, source: &Option<String>) -> Snapshot<T> {
+    match source {
+        Some(source) if value.source().is_none() => value.with_source(source.as_str()),
+        _ => value,
+    }
+}
+
+/// Return `values`, with `source` as the source of any that have none.
+fn bounded_or_source<T>(values: Vec<Bounded<T>>, source: &Option<String>) -> Vec<Bounded<T>> {
+    match source {
+        Some(source) => values
+            .into_iter()
+            .map(|value| match value.source() {
+                None => value.with_source(source.as_str()),
+                Some(_) => value,
+            })
+            .collect(),
+        None => values,
+    }
+}
+
+/// Return `values`, with `source` as the source of any that have none.
+fn eps_or_source(values: Vec<EPSConsensus>, source: &Option<String>) -> Vec<EPSConsensus> {
+    match source {
+        Some(source) => values
+            .into_iter()
+            .map(|value| match value.source() {
+                None => value.with_source(source.as_str()),
+                Some(_) => value,
+            })
+            .collect(),
+        None => values,
+    }
+}
+
+fn money_delta(a: Money, b: Money) -> Option<Money> {
+    if a.currency == b.currency {
+        a.checked_sub(b)
+    } else {
+        None
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::company::{About, RegulatoryFilings, Security};
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicU32, Ordering as AtomicOrdering};
+    use std::time::Duration;
+    use steel_cent::currency::{EUR, USD};
+
+    fn eps(quarter: u8, consensus: i32) -> EPSConsensus {
+        EPSConsensus {
+            consensus: PerShare(Money::of_minor(USD, consensus)),
+            high_estimate: None,
+            low_estimate: None,
+            number_of_estimates: 5,
+            fiscal_period: FinancialPeriod::Quarter {
+                quarter,
+                year: 2019,
+            },
+            fiscal_calendar: FiscalCalendar::default(),
+            fiscal_end_date: Date::from_ymd_opt(2019, u32::from(quarter) * 3, 28).unwrap(),
+            next_report_date: Date::from_ymd_opt(2019, u32::from(quarter) * 3 + 1, 28).unwrap(),
+            published: Date::from_ymd_opt(2019, u32::from(quarter) * 3 - 2, 1).unwrap(),
+            attribution: None,
+        }
+    }
+
+    fn ratings(buy: Counter, hold: Counter, sell: Counter) -> Ratings {
+        let mut ratings = HashMap::new();
+        ratings.insert(RatingType::Buy, buy);
+        ratings.insert(RatingType::Hold, hold);
+        ratings.insert(RatingType::Sell, sell);
+        Ratings {
+            ratings,
+            scale_mark: None,
+        }
+    }
+
+    fn datetime(year: i32, month: u32, day: u32) -> DateTime {
+        Date::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    fn target(high: i32, low: i32, average: i32) -> PriceTarget {
+        PriceTarget {
+            high: PerShare(Money::of_major(USD, high)),
+            low: PerShare(Money::of_major(USD, low)),
+            average: PerShare(Money::of_major(USD, average)),
+            number_of_analysts: 10,
+        }
+    }
+
+    #[test]
+    fn test_rating_type_round_trip() {
+        for rating in &[
+            RatingType::Buy,
+            RatingType::Outperform,
+            RatingType::Hold,
+            RatingType::Underperform,
+            RatingType::Sell,
+        ] {
+            assert_eq!(RatingType::from_str(rating.as_str()).unwrap(), *rating);
+            assert_eq!(rating.to_string(), rating.as_str().to_string());
+        }
+    }
+
+    #[test]
+    fn test_rating_type_from_string() {
+        assert_eq!(RatingType::from_str("Hold").unwrap(), RatingType::Hold);
+        assert_eq!(
+            RatingType::from_str("").err().unwrap(),
+            ParseError::EmptyString
+        );
+        assert_eq!(
+            RatingType::from_str("strong buy").err().unwrap(),
+            ParseError::InvalidRatingString
+        );
+    }
+
+    #[test]
+    fn test_estimate_next_report() {
+        let past = [
+            Date::from_ymd_opt(2019, 3, 31).unwrap(),
+            Date::from_ymd_opt(2019, 6, 30).unwrap(),
+            Date::from_ymd_opt(2019, 9, 30).unwrap(),
+            Date::from_ymd_opt(2019, 12, 31).unwrap(),
+        ];
+        let last = Date::from_ymd_opt(2020, 1, 30).unwrap();
+        assert_eq!(
+            estimate_next_report(&past, last),
+            Some(Date::from_ymd_opt(2020, 5, 1).unwrap())
+        );
+        assert_eq!(estimate_next_report(&past[..1], last), None);
+    }
+
+    #[test]
+    fn test_diff_targets() {
+        let threshold = PerShare(Money::of_major(USD, 1));
+        let prev = target(120, 80, 100);
+        assert_eq!(
+            diff_targets(Some(&prev), Some(&target(120, 80, 105)), threshold),
+            TargetChange::Raised(PerShare(Money::of_major(USD, 5)))
+        );
+        assert_eq!(
+            diff_targets(Some(&prev), Some(&target(120, 80, 95)), threshold),
+            TargetChange::Lowered(PerShare(Money::of_major(USD, -5)))
+        );
+        assert_eq!(
+            diff_targets(Some(&prev), Some(&target(120, 80, 101)), threshold),
+            TargetChange::Unchanged
+        );
+        assert_eq!(
+            diff_targets(None, Some(&prev), threshold),
+            TargetChange::Initiated
+        );
+        assert_eq!(
+            diff_targets(Some(&prev), None, threshold),
+            TargetChange::Dropped
+        );
+
+        let mut euros = target(120, 80, 105);
+        euros.average = PerShare(Money::of_major(EUR, 105));
+        assert_eq!(
+            diff_targets(Some(&prev), Some(&euros), threshold),
+            TargetChange::Incomparable
+        );
+        assert_eq!(
+            diff_targets(Some(&prev), Some(&prev), PerShare(Money::of_major(EUR, 1))),
+            TargetChange::Incomparable
+        );
+        let mut low = prev.clone();
+        low.average = PerShare(Money::of_minor(USD, i32::MIN));
+        let mut high = prev.clone();
+        high.average = PerShare(Money::of_minor(USD, i32::MAX));
+        assert_eq!(
+            diff_targets(Some(&low), Some(&high), threshold),
+            TargetChange::Incomparable
+        );
+    }
+
+    #[test]
+    fn test_check_eps_monotonic() {
+        assert!(check_eps_monotonic(&[eps(2, 110), eps(1, 100), eps(3, 120)]).is_empty());
+        assert_eq!(
+            check_eps_monotonic(&[eps(1, 100), eps(2, 90), eps(3, 120)]),
+            vec![(
+                FinancialPeriod::Quarter {
+                    quarter: 1,
+                    year: 2019
+                },
+                FinancialPeriod::Quarter {
+                    quarter: 2,
+                    year: 2019
+                }
+            )]
+        );
+    }
+
+    struct StaticProvider(AnalystData);
+
+    impl AnalystRecommendations for StaticProvider {
+        fn target_price(&self, _for_symbol: Symbol) -> RequestResult<Snapshot<PriceTarget>> {
+            match &self.0.target {
+                Some(target) => Ok(target.clone()),
+                None => Err(RequestError::Unsupported),
+            }
+        }
+
+        fn consensus_rating(&self, _for_symbol: Symbol) -> RequestResult<Vec<Bounded<Ratings>>> {
+            Ok(self.0.ratings.clone())
+        }
+
+        fn consensus_eps(&self, _for_symbol: Symbol) -> RequestResult<Vec<EPSConsensus>> {
+            Ok(self.0.eps.clone())
+        }
+    }
+
+    fn provider(target: Option<PriceTarget>, ratings: Vec<Ratings>) -> StaticProvider {
+        StaticProvider(AnalystData {
+            target: target.map(|data| Snapshot::new(datetime(2019, 6, 1), data)),
+            ratings: ratings
+                .into_iter()
+                .enumerate()
+                .map(|(i, data)| {
+                    Bounded::new(
+                        datetime(2019, i as u32 + 1, 1),
+                        datetime(2019, i as u32 + 1, 28),
+                        data,
+                    )
+                })
+                .collect(),
+            eps: vec![eps(1, 100), eps(2, 110)],
+        })
+    }
+
+    struct ThreePages;
+
+    impl PagedPeers for ThreePages {
+        fn peers_page(
+            &self,
+            _for_symbol: Symbol,
+            cursor: Option<String>,
+        ) -> RequestResult<(Symbols, Option<String>)> {
+            let page: usize = cursor.map(|c| c.parse().unwrap()).unwrap_or(0);
+            let symbols = vec![format!("P{}A", page), format!("P{}B", page)];
+            let next = if page < 2 {
+                Some((page + 1).to_string())
+            } else {
+                None
+            };
+            Ok((symbols.into_iter().collect(), next))
+        }
+    }
+
+    impl Peers for ThreePages {
+        fn peers(&self, for_symbol: Symbol) -> RequestResult<Symbols> {
+            self.all_peers(for_symbol)
+        }
+    }
+
+    #[test]
+    fn test_paged_peers() {
+        assert_eq!(ThreePages.pages("AAPL".to_string()).count(), 3);
+        assert_eq!(ThreePages.all_peers("AAPL".to_string()).unwrap().len(), 6);
+        assert_eq!(ThreePages.peers("AAPL".to_string()).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn test_scored_peers_default() {
+        let scored = ThreePages.scored_peers("AAPL".to_string()).unwrap();
+        let expected: Vec<(Symbol, f64)> = ["P0A", "P0B", "P1A", "P1B", "P2A", "P2B"]
+            .iter()
+            .map(|peer| (peer.to_string(), 1.0))
+            .collect();
+        assert_eq!(scored, expected);
+    }
+
+    #[test]
+    fn test_price_target_validate() {
+        assert_eq!(target(120, 80, 100).validate(), Ok(()));
+        assert_eq!(
+            target(80, 120, 100).validate(),
+            Err(ValidationError::LowAboveHigh)
+        );
+        assert_eq!(
+            target(120, 80, 130).validate(),
+            Err(ValidationError::AverageOutOfRange)
+        );
+        assert_eq!(
+            target(120, -80, 100).validate(),
+            Err(ValidationError::NegativeValue)
+        );
+    }
+
+    #[test]
+    fn test_with_margin_of_safety() {
+        let original = target(120, 80, 100);
+        assert_eq!(original.with_margin_of_safety(0.0), original);
+        let discounted = original.with_margin_of_safety(0.25);
+        assert_eq!(discounted, target(90, 60, 75));
+        assert_eq!(discounted.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_build_timeline() {
+        let data = AnalystData {
+            target: Some(Snapshot::new(datetime(2019, 5, 1), target(120, 80, 100))),
+            ratings: vec![Bounded::new(
+                datetime(2019, 4, 1),
+                datetime(2019, 4, 30),
+                ratings(5, 3, 1),
+            )],
+            eps: vec![eps(2, 110), eps(1, 100)],
+        };
+        let dates: Vec<Date> = build_timeline(&data).iter().map(|e| e.date()).collect();
+        assert_eq!(
+            dates,
+            vec![
+                Date::from_ymd_opt(2019, 3, 28).unwrap(),
+                Date::from_ymd_opt(2019, 4, 1).unwrap(),
+                Date::from_ymd_opt(2019, 5, 1).unwrap(),
+                Date::from_ymd_opt(2019, 6, 28).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify() {
+        let t = ConsensusThresholds::default();
+        assert_eq!(ratings(5, 1, 0).classify(&t), Some(Sentiment::Bullish));
+        assert_eq!(ratings(1, 5, 1).classify(&t), Some(Sentiment::Neutral));
+        assert_eq!(ratings(0, 1, 5).classify(&t), Some(Sentiment::Bearish));
+        assert_eq!(ratings(0, 0, 0).classify(&t), None);
+    }
+
+    #[test]
+    fn test_peg_from_consensus() {
+        let price = PerShare(Money::of_major(USD, 80));
+        assert_eq!(forward_pe(price, &eps(1, 100)), Some(20.0));
+        assert_eq!(eps_growth(&[eps(2, 125), eps(1, 100)]), Ok(Some(0.25)));
+        assert_eq!(peg_ratio(20.0, 25.0), Some(0.8));
+        assert_eq!(peg_ratio(20.0, 0.0), None);
+        assert_eq!(
+            peg_from_consensus(&[eps(2, 125), eps(1, 100)], price),
+            Some(0.8)
+        );
+    }
+
+    #[test]
+    fn test_forward_pe_annualized() {
+        let price = PerShare(Money::of_major(USD, 80));
+        let mut annual = eps(3, 400);
+        annual.fiscal_period = FinancialPeriod::Year { year: 2019 };
+        let mut half = eps(2, 200);
+        half.fiscal_period = FinancialPeriod::Half {
+            half: 1,
+            year: 2019,
+        };
+        assert_eq!(forward_pe(price, &eps(1, 100)), Some(20.0));
+        assert_eq!(forward_pe(price, &half), Some(20.0));
+        assert_eq!(forward_pe(price, &annual), Some(20.0));
+        assert_eq!(earnings_yield(price, &eps(1, 100)), Some(0.05));
+        assert_eq!(earnings_yield(price, &annual), Some(0.05));
+
+        let euros = PerShare(Money::of_major(EUR, 80));
+        assert_eq!(forward_pe(euros, &annual), None);
+        assert_eq!(earnings_yield(euros, &annual), None);
+        assert_eq!(peg_from_consensus(&[eps(2, 125), eps(1, 100)], euros), None);
+    }
+
+    #[test]
+    fn test_dividend_coverage() {
+        let dividend = |cents, fiscal_period| DividendEstimate {
+            amount: PerShare(Money::of_minor(USD, cents)),
+            fiscal_period,
+            ex_date: Date::from_ymd_opt(2019, 2, 8).unwrap(),
+        };
+        let quarter = FinancialPeriod::Quarter {
+            quarter: 1,
+            year: 2019,
+        };
+        let mut annual = eps(1, 400);
+        annual.fiscal_period = FinancialPeriod::Year { year: 2019 };
+
+        assert_eq!(
+            dividend_coverage(&annual, &dividend(50, quarter.clone())),
+            Some(2.0)
+        );
+        assert_eq!(
+            dividend_coverage(&eps(1, 50), &dividend(100, quarter.clone())),
+            Some(0.5)
+        );
+        assert_eq!(
+            dividend_coverage(
+                &annual,
+                &dividend(
+                    200,
+                    FinancialPeriod::Half {
+                        half: 1,
+                        year: 2019
+                    }
+                )
+            ),
+            Some(1.0)
+        );
+        assert_eq!(dividend_coverage(&annual, &dividend(0, quarter)), None);
+    }
+
+    #[test]
+    fn test_outperform_probability() {
+        assert_eq!(ratings(0, 0, 0).outperform_probability(), None);
+        assert_eq!(ratings(0, 7, 0).outperform_probability(), Some(0.5));
+        assert_eq!(ratings(3, 0, 0).outperform_probability(), Some(1.0));
+        assert_eq!(ratings(0, 0, 3).outperform_probability(), Some(0.0));
+        assert_eq!(ratings(3, 1, 0).outperform_probability(), Some(0.875));
+        let mut mixed = ratings(0, 0, 0);
+        mixed.ratings.insert(RatingType::Outperform, 1);
+        mixed.ratings.insert(RatingType::Underperform, 1);
+        mixed.ratings.insert(RatingType::Sell, 2);
+        assert_eq!(mixed.outperform_probability(), Some(0.25));
+    }
+
+    #[test]
+    fn test_validate_batch() {
+        let summary = validate_batch(&[
+            target(120, 80, 100),
+            target(80, 120, 130),
+            target(120, 80, 130),
+        ]);
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.valid, 1);
+        assert_eq!(summary.by_error.len(), 2);
+        assert_eq!(summary.by_error[&ValidationError::LowAboveHigh], 1);
+        assert_eq!(summary.by_error[&ValidationError::AverageOutOfRange], 2);
+
+        let mut invalid = eps(1, 100);
+        invalid.number_of_estimates = 0;
+        let summary = validate_batch(&[eps(1, 100), invalid]);
+        assert_eq!(summary.valid, 1);
+        assert_eq!(summary.by_error[&ValidationError::NoEstimates], 1);
+
+        assert_eq!(
+            validate_batch::<PriceTarget>(&[]),
+            ValidationSummary::default()
+        );
+    }
+
+    #[test]
+    fn test_align_to_dates() {
+        let period = |start, end, data| {
+            Bounded::new(datetime(2019, start, 1), datetime(2019, end, 28), data)
+        };
+        let series = [period(1, 3, "Q1"), period(4, 6, "Q2"), period(3, 4, "late")];
+        let date = |month, day| Date::from_ymd_opt(2019, month, day).unwrap();
+        assert_eq!(
+            align_to_dates(
+                &series,
+                &[
+                    date(1, 1),
+                    date(2, 15),
+                    date(3, 28),
+                    date(4, 1),
+                    date(5, 1),
+                    date(7, 1)
+                ]
+            ),
+            vec![
+                Some("Q1"),
+                Some("Q1"),
+                Some("late"),
+                Some("Q2"),
+                Some("Q2"),
+                None
+            ]
+        );
+        assert!(align_to_dates::<()>(&[], &[date(1, 1)])[0].is_none());
+    }
+
+    #[test]
+    fn test_position_in_range() {
+        let usd = |major| PerShare(Money::of_major(USD, major));
+        let t = target(120, 80, 100);
+        assert_eq!(
+            t.position_in_range(usd(50), usd(150), TargetBound::Average),
+            0.5
+        );
+        assert_eq!(
+            t.position_in_range(usd(50), usd(150), TargetBound::High),
+            0.7
+        );
+        assert_eq!(
+            t.position_in_range(usd(90), usd(110), TargetBound::Low),
+            0.0
+        );
+        assert_eq!(
+            t.position_in_range(usd(90), usd(110), TargetBound::High),
+            1.0
+        );
+        assert_eq!(
+            t.position_in_range(usd(100), usd(100), TargetBound::Average),
+            0.5
+        );
+        assert_eq!(
+            t.position_in_range(usd(100), usd(100), TargetBound::High),
+            1.0
+        );
+        assert_eq!(
+            t.position_in_range(usd(100), usd(100), TargetBound::Low),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(
+            target(120, 80, 100).fingerprint(),
+            target(120, 80, 100).fingerprint()
+        );
+        assert_ne!(
+            target(120, 80, 100).fingerprint(),
+            target(120, 80, 101).fingerprint()
+        );
+        assert_eq!(eps(1, 100).fingerprint(), eps(1, 100).fingerprint());
+        let mut later = eps(1, 100);
+        later.published = later.published.succ_opt().unwrap();
+        assert_ne!(eps(1, 100).fingerprint(), later.fingerprint());
+
+        // built in different orders, so the maps iterate differently.
+        let mut a = HashMap::new();
+        let mut b = HashMap::new();
+        for (rating, count) in [
+            (RatingType::Buy, 3),
+            (RatingType::Hold, 2),
+            (RatingType::Sell, 1),
+        ] {
+            a.insert(rating, count);
+        }
+        for (rating, count) in [
+            (RatingType::Sell, 1),
+            (RatingType::Hold, 2),
+            (RatingType::Buy, 3),
+        ] {
+            b.insert(rating, count);
+        }
+        let a = Ratings {
+            ratings: a,
+            scale_mark: Some(2.0),
+        };
+        let mut b = Ratings {
+            ratings: b,
+            scale_mark: Some(2.0),
+        };
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        b.scale_mark = None;
+        assert_ne!(a.fingerprint(), b.fingerprint());
+        assert_ne!(
+            ratings(1, 2, 0).fingerprint(),
+            ratings(2, 1, 0).fingerprint()
+        );
+    }
+
+    #[test]
+    fn test_consensus_eps_at() {
+        let static_provider = provider(None, Vec::new());
+        let symbol = "AAPL".to_string();
+        let at = |y, m, d| {
+            static_provider
+                .consensus_eps_at(symbol.clone(), Date::from_ymd_opt(y, m, d).unwrap())
+                .unwrap()
+        };
+        assert_eq!(at(2018, 12, 31), None);
+        assert_eq!(at(2019, 1, 1), Some(vec![eps(1, 100)]));
+        assert_eq!(at(2019, 3, 31), Some(vec![eps(1, 100)]));
+        assert_eq!(at(2019, 4, 1), Some(vec![eps(1, 100), eps(2, 110)]));
+    }
+
+    #[test]
+    fn test_merging_recommendations() {
+        let ratings_only = provider(None, vec![ratings(5, 3, 1)]);
+        let targets_only = provider(Some(target(120, 80, 100)), Vec::new());
+        let merged = MergingRecommendations::new(ratings_only, targets_only);
+        let symbol = "AAPL".to_string();
+        assert_eq!(
+            merged.target_price(symbol.clone()).unwrap().data,
+            target(120, 80, 100)
+        );
+        assert_eq!(
+            merged.consensus_rating(symbol.clone()).unwrap()[0].data,
+            ratings(5, 3, 1)
+        );
+        assert_eq!(merged.consensus_eps(symbol.clone()).unwrap().len(), 2);
+        assert!(matches!(
+            merged.analyst_estimates(symbol.clone()),
+            Err(RequestError::Unsupported)
+        ));
+        assert_eq!(merged.target_price(symbol.clone()).unwrap().source(), None);
+
+        let merged = merged.with_sources("ratings", "targets");
+        assert_eq!(
+            merged.target_price(symbol.clone()).unwrap().source(),
+            Some("targets")
+        );
+        assert_eq!(
+            merged.consensus_rating(symbol.clone()).unwrap()[0].source(),
+            Some("ratings")
+        );
+        let merged_eps = merged.consensus_eps(symbol.clone()).unwrap();
+        assert_eq!(merged_eps[0].source(), Some("ratings"));
+        assert_eq!(
+            merged_eps[0].attribution.as_ref().unwrap().as_of,
+            merged_eps[0].published
+        );
+        let as_of = Date::from_ymd_opt(2019, 12, 31).unwrap();
+        assert_eq!(
+            merged
+                .consensus_eps_at(symbol.clone(), as_of)
+                .unwrap()
+                .unwrap()[0]
+                .source(),
+            Some("ratings")
+        );
+
+        let mut sourced = provider(Some(target(120, 80, 100)), Vec::new());
+        sourced.0.target = sourced.0.target.map(|t| t.with_source("IEX"));
+        sourced.0.eps[0] = sourced.0.eps[0].clone().with_source("IEX");
+        let merged =
+            MergingRecommendations::new(sourced, provider(None, Vec::new())).with_sources("a", "b");
+        assert_eq!(
+            merged.target_price(symbol.clone()).unwrap().source(),
+            Some("IEX")
+        );
+        let merged_eps = merged.consensus_eps(symbol.clone()).unwrap();
+        assert_eq!(merged_eps[0].source(), Some("IEX"));
+        assert_eq!(merged_eps[1].source(), Some("a"));
+
+        let merged = MergingRecommendations::new(provider(None, Vec::new()), BySymbol);
+        assert_eq!(merged.consensus_rating(symbol).unwrap().len(), 1);
+        assert!(merged
+            .consensus_rating("BAD".to_string())
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            merged.target_price("BAD".to_string()),
+            Err(RequestError::Unsupported)
+        ));
+    }
+
+    #[test]
+    fn test_rating_type_trend() {
+        let period =
+            |month, data| Bounded::new(datetime(2019, month, 1), datetime(2019, month, 28), data);
+        let mut no_buys = ratings(0, 4, 1);
+        no_buys.ratings.remove(&RatingType::Buy);
+        let history = [
+            period(3, ratings(6, 2, 1)),
+            period(1, no_buys),
+            period(2, ratings(3, 3, 1)),
+        ];
+        let date = |month| Date::from_ymd_opt(2019, month, 1).unwrap();
+        assert_eq!(
+            rating_type_trend(&history, RatingType::Buy),
+            vec![(date(1), 0), (date(2), 3), (date(3), 6)]
+        );
+        assert_eq!(
+            rating_type_trend(&history, RatingType::Outperform),
+            vec![(date(1), 0), (date(2), 0), (date(3), 0)]
+        );
+    }
+
+    #[test]
+    fn test_conviction_index() {
+        assert_eq!(conviction_index(&ratings(0, 0, 0)), None);
+        let unanimous = conviction_index(&ratings(40, 0, 0)).unwrap();
+        assert!(unanimous > 0.97 && unanimous <= 1.0);
+
+        let mut even = ratings(8, 8, 8);
+        even.ratings.insert(RatingType::Outperform, 8);
+        even.ratings.insert(RatingType::Underperform, 8);
+        assert!(conviction_index(&even).unwrap().abs() < 1e-9);
+
+        let clustered = conviction_index(&ratings(20, 20, 0)).unwrap();
+        let scattered = conviction_index(&ratings(14, 13, 13)).unwrap();
+        assert!(clustered > scattered);
+        assert!(conviction_index(&ratings(1, 0, 0)).unwrap() < unanimous);
+    }
+
+    #[test]
+    fn test_rating_scorers() {
+        let holds = ratings(0, 4, 0);
+        assert_eq!(LinearScorer.score(&holds), Some(0.5));
+        assert_eq!(ExponentialScorer::default().score(&holds), Some(0.2));
+        assert_eq!(
+            ExponentialScorer::default().score(&ratings(2, 0, 0)),
+            Some(1.0)
+        );
+        assert_eq!(
+            ExponentialScorer::default().score(&ratings(0, 0, 2)),
+            Some(0.0)
+        );
+        assert_eq!(ExponentialScorer { base: 1.0 }.score(&holds), Some(0.5));
+        assert_eq!(ExponentialScorer::default().score(&ratings(0, 0, 0)), None);
+
+        let data = AnalystData {
+            target: None,
+            ratings: vec![Bounded::new(
+                datetime(2019, 1, 1),
+                datetime(2019, 1, 31),
+                holds,
+            )],
+            eps: Vec::new(),
+        };
+        let weights = ScoreWeights {
+            rating: 1.0,
+            target_upside: 1.0,
+            eps_growth: 1.0,
+        };
+        let price = PerShare(Money::of_major(USD, 100));
+        assert_eq!(composite_score(&data, price, &weights), Some(0.5));
+        assert_eq!(
+            composite_score_with(&data, price, &weights, &ExponentialScorer::default()),
+            Some(0.2)
+        );
+    }
+
+    #[test]
+    fn test_consensus_store_diffs() {
+        let symbol = "AAPL".to_string();
+        let mut store = ConsensusStore::new();
+        assert_eq!(store.record_and_diff(symbol.clone(), eps(1, 100)), None);
+        let mut revised = eps(1, 90);
+        revised.published = Date::from_ymd_opt(2019, 2, 1).unwrap();
+        assert_eq!(
+            store.record_and_diff(symbol.clone(), revised),
+            Some(EpsChange {
+                fiscal_period: eps(1, 0).fiscal_period,
+                previous: PerShare(Money::of_minor(USD, 100)),
+                current: PerShare(Money::of_minor(USD, 90)),
+                delta: Some(PerShare(Money::of_minor(USD, -10))),
+            })
+        );
+        assert_eq!(store.record_and_diff(symbol.clone(), eps(2, 110)), None);
+
+        let threshold = PerShare(Money::of_major(USD, 1));
+        let snapshot =
+            |month, average| Snapshot::new(datetime(2019, month, 1), target(120, 80, average));
+        assert_eq!(
+            store.record_target_and_diff(symbol.clone(), snapshot(1, 100), threshold),
+            TargetChange::Initiated
+        );
+        assert_eq!(
+            store.record_target_and_diff(symbol.clone(), snapshot(2, 110), threshold),
+            TargetChange::Raised(PerShare(Money::of_major(USD, 10)))
+        );
+        assert_eq!(
+            store.record_target_and_diff(symbol.clone(), snapshot(3, 110), threshold),
+            TargetChange::Unchanged
+        );
+        let mut euros = target(120, 80, 100);
+        euros.average = PerShare(Money::of_major(EUR, 100));
+        assert_eq!(
+            store.record_target_and_diff(
+                symbol.clone(),
+                Snapshot::new(datetime(2019, 4, 1), euros),
+                threshold
+            ),
+            TargetChange::Incomparable
+        );
+
+        let history = store.history(
+            symbol,
+            Date::from_ymd_opt(2019, 1, 1).unwrap(),
+            Date::from_ymd_opt(2019, 2, 28).unwrap(),
+        );
+        assert_eq!(
+            history.target.unwrap().data.average,
+            PerShare(Money::of_major(USD, 110))
+        );
+        assert_eq!(history.eps.len(), 2);
+    }
+
+    #[test]
+    fn test_risk_adjusted_target() {
+        let current = PerShare(Money::of_major(USD, 80));
+        let unanimous = target(100, 100, 100);
+        assert_eq!(
+            unanimous.risk_adjusted_target(current, 0.0),
+            Some(PerShare(Money::of_major(USD, 100)))
+        );
+        // agreement 0.6, and 10 / (10 + 10) analysts, gives a weight of 0.3.
+        assert_eq!(
+            target(120, 80, 100).risk_adjusted_target(current, 10.0),
+            Some(PerShare(Money::of_major(USD, 86)))
+        );
+        let mut uncovered = target(120, 80, 100);
+        uncovered.number_of_analysts = 0;
+        assert_eq!(uncovered.risk_adjusted_target(current, 5.0), Some(current));
+        assert_eq!(
+            target(200, 0, 100).risk_adjusted_target(current, 0.0),
+            Some(current)
+        );
+
+        let euros = PerShare(Money::of_major(EUR, 80));
+        assert_eq!(unanimous.risk_adjusted_target(euros, 0.0), None);
+        let mut mixed = target(120, 80, 100);
+        mixed.low = PerShare(Money::of_major(EUR, 80));
+        assert_eq!(mixed.risk_adjusted_target(current, 0.0), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_analyst_data_json() {
+        let mut data = provider(Some(target(120, 80, 100)), vec![ratings(5, 3, 1)]).0;
+        data.ratings[0].data.scale_mark = Some(1.5);
+        data.eps[0].fiscal_period = FinancialPeriod::Half {
+            half: 1,
+            year: 2019,
+        };
+        let json = data.to_json().unwrap();
+        assert!(json.starts_with(r#"{"schema_version":1,"#));
+        assert!(json.contains(r#"{"currency":"USD","minor_amount":12000}"#));
+        assert!(json.contains(r#""fiscal_period":"H1 2019""#));
+        assert_eq!(AnalystData::from_json(&json).unwrap(), data);
+
+        let extended = json.replacen('{', r#"{"extra":[1,2,3],"#, 1);
+        assert_eq!(AnalystData::from_json(&extended).unwrap(), data);
+
+        let future = json.replacen(r#""schema_version":1"#, r#""schema_version":2"#, 1);
+        assert!(AnalystData::from_json(&future).is_err());
+        assert!(AnalystData::from_json(r#"{"target":null,"ratings":[],"eps":[]}"#).is_err());
+    }
+
+    #[test]
+    fn test_rating_change_since() {
+        let symbol = "AAPL".to_string();
+        let reco = provider(
+            None,
+            vec![ratings(1, 1, 1), ratings(4, 1, 0), ratings(4, 1, 0)],
+        );
+        let date = |month, day| Date::from_ymd_opt(2019, month, day).unwrap();
+        let change = reco
+            .rating_change_since(symbol.clone(), date(1, 15))
+            .unwrap()
+            .unwrap();
+        assert_eq!(change.date, date(3, 1));
+        assert_eq!(change.direction, Direction::Upgrade);
+        assert!((change.magnitude - (3.0 - 1.4)).abs() < 1e-9);
+
+        assert!(reco
+            .rating_change_since(symbol.clone(), date(2, 15))
+            .unwrap()
+            .is_none());
+        assert!(reco
+            .rating_change_since(symbol.clone(), date(1, 1))
+            .unwrap()
+            .is_some());
+        let before = Date::from_ymd_opt(2018, 12, 1).unwrap();
+        assert!(reco.rating_change_since(symbol, before).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_target_histogram() {
+        let usd = |major| Money::of_major(USD, major);
+        let targets = [usd(102), usd(95), usd(131), usd(109), usd(100)];
+        assert_eq!(
+            target_histogram(&targets, usd(10)),
+            Some(vec![
+                (usd(90), 1),
+                (usd(100), 3),
+                (usd(110), 0),
+                (usd(120), 0),
+                (usd(130), 1)
+            ])
+        );
+        assert_eq!(target_histogram(&[], usd(10)), Some(Vec::new()));
+        assert_eq!(target_histogram(&targets, usd(0)), None);
+        assert_eq!(target_histogram(&targets, usd(-10)), None);
+        assert_eq!(target_histogram(&targets, Money::of_major(EUR, 10)), None);
+
+        // a range of more than `MAX_HISTOGRAM_BUCKETS` is not allocated
+        let cent = Money::of_minor(USD, 1);
+        let wide = [
+            Money::of_minor(USD, i32::MIN),
+            Money::of_minor(USD, i32::MAX),
+        ];
+        assert_eq!(target_histogram(&wide, cent), None);
+        assert_eq!(
+            target_histogram(&[usd(0), Money::of_minor(USD, 9_999)], cent).map(|h| h.len()),
+            Some(MAX_HISTOGRAM_BUCKETS)
+        );
+        assert_eq!(target_histogram(&[usd(0), usd(100)], cent), None);
+
+        // the lower bound of the bucket holding `i32::MIN` is below `i32::MIN`
+        let lowest = [Money::of_minor(USD, i32::MIN)];
+        assert_eq!(target_histogram(&lowest, Money::of_minor(USD, 10)), None);
+        assert_eq!(
+            target_histogram(&lowest, Money::of_minor(USD, 2)),
+            Some(vec![(Money::of_minor(USD, i32::MIN), 1)])
+        );
+    }
+
+    #[test]
+    fn test_reconcile_fiscal_date() {
+        let calendar = FiscalCalendar::default();
+        let mut consistent = eps(1, 100);
+        assert_eq!(consistent.reconcile_fiscal_date(&calendar), None);
+        assert_eq!(consistent, eps(1, 100));
+
+        let mut wrong = eps(1, 100);
+        wrong.fiscal_end_date = Date::from_ymd_opt(2019, 4, 2).unwrap();
+        assert_eq!(
+            wrong.reconcile_fiscal_date(&calendar),
+            Some(Date::from_ymd_opt(2019, 4, 2).unwrap())
+        );
+        assert_eq!(
+            wrong.fiscal_end_date,
+            Date::from_ymd_opt(2019, 3, 31).unwrap()
+        );
+
+        let october = FiscalCalendar { start_month: 10 };
+        let mut fiscal = eps(1, 100);
+        assert!(fiscal.reconcile_fiscal_date(&october).is_some());
+        assert_eq!(
+            fiscal.fiscal_end_date,
+            Date::from_ymd_opt(2018, 12, 31).unwrap()
+        );
+        assert_eq!(fiscal.fiscal_calendar, october);
+        assert_eq!(fiscal.reconcile_fiscal_date(&october), None);
+
+        let mut invalid = eps(1, 100);
+        invalid.fiscal_period = FinancialPeriod::Quarter {
+            quarter: 0,
+            year: 2019,
+        };
+        assert_eq!(invalid.reconcile_fiscal_date(&calendar), None);
+        assert_eq!(invalid.fiscal_end_date, eps(1, 100).fiscal_end_date);
+    }
+
+    #[test]
+    fn test_negative_earnings() {
+        let price = PerShare(Money::of_major(USD, 20));
+        let loss = eps(1, -50);
+        assert!(!loss.is_profitable());
+        assert!(!eps(1, 0).is_profitable());
+        assert!(eps(1, 1).is_profitable());
+        assert_eq!(forward_pe(price, &loss), None);
+        assert_eq!(forward_pe(price, &eps(1, 0)), None);
+        assert_eq!(earnings_yield(price, &loss), Some(-0.1));
+        assert_eq!(earnings_yield(price, &eps(1, 100)), Some(0.2));
+        assert_eq!(
+            earnings_yield(PerShare(Money::of_major(USD, 0)), &loss),
+            None
+        );
+        assert_eq!(peg_ratio(-20.0, 25.0), None);
+        assert_eq!(peg_from_consensus(&[eps(2, 50), loss], price), None);
+    }
+
+    #[test]
+    fn test_scaled_average_large_counts() {
+        let mut large = ratings(Counter::MAX, 0, Counter::MAX);
+        large.ratings.insert(RatingType::Underperform, Counter::MAX);
+        assert_eq!(large.scaled_average(), 10.0 / 3.0);
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let a = provider(Some(target(120, 80, 100)), vec![ratings(5, 5, 0)]);
+        let b = provider(None, vec![ratings(9, 9, 9), ratings(0, 5, 5)]);
+        let report = reconcile(&a, &b, "AAPL".to_string()).unwrap();
+        assert_eq!(
+            report,
+            ReconciliationReport {
+                target_delta: None,
+                rating_delta: Some(-2.0),
+                eps_delta: Some(PerShare(Money::zero(USD))),
+            }
+        );
+    }
+
+    #[test]
+    fn test_reconstruct_ratings() {
+        let event = |firm: &str, from, to, day| RatingEvent {
+            firm: firm.to_string(),
+            from,
+            to,
+            date: Date::from_ymd_opt(2019, 1, day).unwrap(),
+        };
+        let events = vec![
+            event("B", Some(RatingType::Buy), RatingType::Hold, 10),
+            event("A", None, RatingType::Buy, 1),
+            event("B", None, RatingType::Buy, 2),
+            event("C", None, RatingType::Sell, 20),
+        ];
+        let ratings = reconstruct_ratings(&events, Date::from_ymd_opt(2019, 1, 15).unwrap());
+        assert_eq!(ratings.ratings.get(&RatingType::Buy), Some(&1));
+        assert_eq!(ratings.ratings.get(&RatingType::Hold), Some(&1));
+        assert_eq!(ratings.ratings.get(&RatingType::Sell), None);
+    }
+
+    #[test]
+    fn test_composite_score() {
+        let weights = ScoreWeights {
+            rating: 2.0,
+            target_upside: 1.0,
+            eps_growth: 1.0,
+        };
+        let mut data = provider(Some(target(150, 100, 120)), vec![ratings(1, 0, 0)]).0;
+        data.eps = vec![eps(1, 100), eps(2, 100)];
+        let current = PerShare(Money::of_major(USD, 100));
+        assert_eq!(target(150, 100, 120).upside(current), Some(0.2));
+        assert_eq!(
+            composite_score(&data, current, &weights),
+            Some((2.0 * 1.0 + 0.6 + 0.5) / 4.0)
+        );
+        data.target = None;
+        assert_eq!(
+            composite_score(&data, current, &weights),
+            Some((2.0 * 1.0 + 0.5) / 3.0)
+        );
+    }
+
+    #[test]
+    fn test_top_and_bottom_n() {
+        let scored: Vec<(Symbol, f64)> = vec![
+            ("D".to_string(), 0.4),
+            ("A".to_string(), 0.9),
+            ("C".to_string(), 0.4),
+            ("B".to_string(), 0.1),
+        ];
+        let names = |v: Vec<(Symbol, f64)>| v.into_iter().map(|(s, _)| s).collect::<Vec<_>>();
+        assert_eq!(names(top_n(&scored, 3)), vec!["A", "C", "D"]);
+        assert_eq!(names(bottom_n(&scored, 2)), vec!["B", "C"]);
+        assert_eq!(top_n(&scored, 10).len(), 4);
+        assert!(top_n(&scored, 0).is_empty());
+
+        let mut with_nan = scored;
+        with_nan.push(("E".to_string(), f64::NAN));
+        with_nan.push(("F".to_string(), 0.5));
+        assert_eq!(names(top_n(&with_nan, 3)), vec!["A", "F", "C"]);
+        assert_eq!(names(bottom_n(&with_nan, 3)), vec!["B", "C", "D"]);
+        assert_eq!(
+            names(top_n(&with_nan, 6)),
+            vec!["A", "F", "C", "D", "B", "E"]
+        );
+        assert_eq!(
+            names(bottom_n(&with_nan, 6)),
+            vec!["B", "C", "D", "F", "A", "E"]
+        );
+    }
+
+    #[test]
+    fn test_mixed_fiscal_calendars() {
+        let mut april = eps(2, 125);
+        april.fiscal_calendar = FiscalCalendar { start_month: 4 };
+        assert_eq!(
+            eps_growth(&[april.clone(), eps(1, 100)]),
+            Err(AnalysisError::MixedFiscalCalendars)
+        );
+        assert_eq!(
+            consolidate_eps(&[april, eps(1, 100)]),
+            Err(AnalysisError::MixedFiscalCalendars)
+        );
+    }
+
+    #[test]
+    fn test_consolidate_eps() {
+        let mut other = eps(1, 130);
+        other.number_of_estimates = 10;
+        let consolidated = consolidate_eps(&[eps(2, 110), eps(1, 100), other]).unwrap();
+        assert_eq!(consolidated.len(), 2);
+        assert_eq!(
+            consolidated[0].consensus,
+            PerShare(Money::of_minor(USD, 120))
+        );
+        assert_eq!(consolidated[0].number_of_estimates, 15);
+        assert_eq!(consolidated[1], eps(2, 110));
+
+        let mut first = eps(1, 100).with_estimate_range(
+            PerShare(Money::of_minor(USD, 120)),
+            PerShare(Money::of_minor(USD, 90)),
+        );
+        first.number_of_estimates = 0;
+        let mut second = eps(1, 120).with_estimate_range(
+            PerShare(Money::of_minor(USD, 130)),
+            PerShare(Money::of_minor(USD, 95)),
+        );
+        second.number_of_estimates = 0;
+        let consolidated = consolidate_eps(&[first, second]).unwrap();
+        assert_eq!(consolidated.len(), 1);
+        assert_eq!(
+            consolidated[0].consensus,
+            PerShare(Money::of_minor(USD, 110))
+        );
+        assert_eq!(consolidated[0].number_of_estimates, 0);
+        assert_eq!(
+            consolidated[0].high_estimate,
+            Some(PerShare(Money::of_minor(USD, 130)))
+        );
+        assert_eq!(
+            consolidated[0].low_estimate,
+            Some(PerShare(Money::of_minor(USD, 90)))
+        );
+
+        let mut many = eps(1, 100);
+        many.number_of_estimates = Counter::MAX;
+        let consolidated = consolidate_eps(&[many.clone(), many]).unwrap();
+        assert_eq!(consolidated[0].number_of_estimates, Counter::MAX);
+        assert_eq!(
+            consolidated[0].consensus,
+            PerShare(Money::of_minor(USD, 100))
+        );
+
+        let mut euros = eps(1, 100);
+        euros.consensus = PerShare(Money::of_minor(EUR, 100));
+        assert_eq!(
+            consolidate_eps(&[eps(1, 100), euros]),
+            Err(AnalysisError::MixedCurrencies)
+        );
+        let mut euro_range = eps(2, 110);
+        euro_range.low_estimate = Some(PerShare(Money::of_minor(EUR, 90)));
+        assert_eq!(
+            consolidate_eps(&[eps(1, 100), euro_range]),
+            Err(AnalysisError::MixedCurrencies)
+        );
+
+        let iex = |eps: EPSConsensus| eps.with_source("IEX");
+        let consolidated = consolidate_eps(&[iex(eps(1, 100)), iex(eps(1, 120))]).unwrap();
+        assert_eq!(consolidated[0].source(), Some("IEX"));
+        let consolidated = consolidate_eps(&[iex(eps(1, 100)), eps(1, 120)]).unwrap();
+        assert_eq!(consolidated[0].attribution, None);
+    }
+
+    #[test]
+    fn test_agreement() {
+        assert_eq!(target(100, 100, 100).agreement(), 1.0);
+        assert_eq!(target(120, 80, 100).agreement(), 0.6);
+        assert_eq!(target(300, 50, 100).agreement(), 0.0);
+        assert_eq!(target(10, 0, 0).agreement(), 0.0);
+    }
+
+    #[test]
+    fn test_price_target_try_new() {
+        let usd = |major| PerShare(Money::of_major(USD, major));
+        assert_eq!(
+            PriceTarget::try_new(usd(120), usd(80), usd(100), 10),
+            Ok(target(120, 80, 100))
+        );
+        assert_eq!(
+            PriceTarget::try_new(usd(80), usd(120), usd(100), 10),
+            Err(ValidationError::LowAboveHigh)
+        );
+        assert_eq!(
+            PriceTarget::try_new(usd(120), usd(80), usd(130), 10),
+            Err(ValidationError::AverageOutOfRange)
+        );
+        assert_eq!(
+            PriceTarget::try_new(usd(120), usd(-80), usd(100), 10),
+            Err(ValidationError::NegativeValue)
+        );
+        assert_eq!(
+            PriceTarget::try_new(usd(120), PerShare(Money::of_major(EUR, 80)), usd(100), 10),
+            Err(ValidationError::CurrencyMismatch)
+        );
+    }
+
+    #[test]
+    fn test_eps_consensus_try_new() {
+        let valid = eps(1, 100);
+        let try_new = |eps: EPSConsensus| {
+            EPSConsensus::try_new(
+                eps.consensus,
+                eps.number_of_estimates,
+                eps.fiscal_period,
+                eps.fiscal_calendar,
+                eps.fiscal_end_date,
+                eps.next_report_date,
+                eps.published,
+            )
+        };
+        assert_eq!(try_new(valid.clone()), Ok(valid.clone()));
+
+        let mut invalid = valid.clone();
+        invalid.fiscal_period = FinancialPeriod::Quarter {
+            quarter: 5,
+            year: 2019,
+        };
+        assert_eq!(try_new(invalid), Err(ValidationError::InvalidFiscalPeriod));
+
+        let mut half = valid.clone();
+        half.fiscal_period = FinancialPeriod::Half {
+            half: 1,
+            year: 2019,
+        };
+        assert_eq!(try_new(half.clone()), Ok(half));
+
+        let mut invalid = valid.clone();
+        invalid.number_of_estimates = 0;
+        assert_eq!(try_new(invalid), Err(ValidationError::NoEstimates));
+
+        let mut invalid = valid;
+        invalid.next_report_date = Date::from_ymd_opt(2019, 1, 1).unwrap();
+        assert_eq!(
+            try_new(invalid),
+            Err(ValidationError::ReportBeforeFiscalEnd)
+        );
+    }
+
+    #[test]
+    fn test_consensus_store() {
+        let symbol = "AAPL".to_string();
+        let mut store = ConsensusStore::new();
+        store.record(symbol.clone(), eps(3, 120));
+        store.record(symbol.clone(), eps(1, 100));
+        store.record(symbol.clone(), eps(1, 100));
+        store.record(symbol.clone(), eps(2, 110));
+        store.record("MSFT".to_string(), eps(2, 200));
+
+        let bounded = |buy, start, end| {
+            Bounded::new(
+                datetime(2019, start, 1),
+                datetime(2019, end, 1),
+                ratings(buy, 2, 1),
+            )
+        };
+        store.record_ratings(symbol.clone(), bounded(5, 4, 5));
+        store.record_ratings(symbol.clone(), bounded(4, 1, 2));
+        store.record_ratings(symbol.clone(), bounded(4, 1, 2));
+
+        let all = store.history(
+            symbol.clone(),
+            Date::from_ymd_opt(2019, 1, 1).unwrap(),
+            Date::from_ymd_opt(2019, 12, 31).unwrap(),
+        );
+        assert!(all.target.is_none());
+        let cents: Vec<i32> = all
+            .eps
+            .iter()
+            .map(|e| e.consensus.0.minor_amount())
+            .collect();
+        assert_eq!(cents, vec![100, 110, 120]);
+        assert_eq!(all.ratings.len(), 2);
+        assert_eq!(all.ratings[0].data, ratings(4, 2, 1));
+
+        let some = store.history(
+            symbol,
+            Date::from_ymd_opt(2019, 4, 1).unwrap(),
+            Date::from_ymd_opt(2019, 4, 30).unwrap(),
+        );
+        assert_eq!(some.eps, vec![eps(2, 110)]);
+        assert_eq!(some.ratings.len(), 1);
+
+        let none = store.history(
+            "IBM".to_string(),
+            Date::from_ymd_opt(2019, 1, 1).unwrap(),
+            Date::from_ymd_opt(2019, 12, 31).unwrap(),
+        );
+        assert!(none.eps.is_empty() && none.ratings.is_empty());
+    }
+
+    #[test]
+    fn test_peer_relative_zscore() {
+        let reco = provider(None, Vec::new());
+        let usd = |major| PerShare(Money::of_major(USD, major));
+        let mut prices = HashMap::new();
+        prices.insert("AAPL".to_string(), usd(12));
+        prices.insert("P0A".to_string(), usd(10));
+        let zscore = |prices: &HashMap<Symbol, PerShare>| {
+            peer_relative_zscore(&ThreePages, &reco, "AAPL".to_string(), prices).unwrap()
+        };
+        assert_eq!(zscore(&prices), None);
+
+        prices.insert("P0B".to_string(), usd(20));
+        prices.insert("P1A".to_string(), usd(30));
+        let z = zscore(&prices).unwrap();
+        assert!((z - (12.0 - 20.0) / (200.0f64 / 3.0).sqrt()).abs() < 1e-9);
+
+        prices.remove("AAPL");
+        assert_eq!(zscore(&prices), None);
+    }
+
+    #[test]
+    fn test_ratings_merge() {
+        let mut a = ratings(2, 1, 0);
+        let mut b = ratings(1, 1, 1);
+        let merged = a.merge(&b);
+        assert_eq!(merged.ratings[&RatingType::Buy], 3);
+        assert_eq!(merged.ratings[&RatingType::Sell], 1);
+        assert_eq!(merged.scale_mark, None);
+
+        a.scale_mark = Some(2.0);
+        b.scale_mark = Some(2.00001);
+        assert_eq!(a.merge(&b).scale_mark, Some(2.0));
+        b.scale_mark = Some(2.5);
+        assert_eq!(a.merge(&b).scale_mark, None);
+
+        let mut merged = a.merge(&b);
+        merged.recompute_scale_mark();
+        assert_eq!(merged.scale_mark, Some(merged.scaled_average() as f32));
+        let mut empty = ratings(0, 0, 0);
+        empty.scale_mark = Some(1.0);
+        empty.recompute_scale_mark();
+        assert_eq!(empty.scale_mark, None);
+    }
+
+    struct BySymbol;
+
+    impl AnalystRecommendations for BySymbol {
+        fn target_price(&self, _for_symbol: Symbol) -> RequestResult<Snapshot<PriceTarget>> {
+            Err(RequestError::Unsupported)
+        }
+
+        fn consensus_rating(&self, for_symbol: Symbol) -> RequestResult<Vec<Bounded<Ratings>>> {
+            match for_symbol.as_str() {
+                "BAD" => Err(RequestError::BadSymbolError(for_symbol)),
+                "NONE" => Ok(Vec::new()),
+                "OTC" => Err(RequestError::Unsupported),
+                "DOWN" => Err(RequestError::CommunicationError),
+                _ => Ok(vec![Bounded::new(
+                    datetime(2019, 1, 1),
+                    datetime(2019, 1, 31),
+                    ratings(3, 2, 1),
+                )]),
+            }
+        }
+
+        fn consensus_eps(&self, _for_symbol: Symbol) -> RequestResult<Vec<EPSConsensus>> {
+            Err(RequestError::Unsupported)
+        }
+    }
+
+    #[test]
+    fn test_consensus_rating_many() {
+        let symbols = vec!["AAPL".to_string(), "BAD".to_string(), "NONE".to_string()];
+        let results = BySymbol.consensus_rating_many(&symbols).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results["AAPL"].as_ref().unwrap().len(), 1);
+        assert!(matches!(
+            results["BAD"],
+            Err(RequestError::BadSymbolError(_))
+        ));
+        assert!(!results.contains_key("NONE"));
+
+        let results = BySymbol
+            .consensus_rating_many(&["AAPL".to_string(), "OTC".to_string()])
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results["AAPL"].as_ref().unwrap().len(), 1);
+        assert!(matches!(results["OTC"], Err(RequestError::Unsupported)));
+
+        assert!(matches!(
+            BySymbol.consensus_rating_many(&["OTC".to_string(), "AAPL".to_string()]),
+            Err(RequestError::Unsupported)
+        ));
+    }
+
+    #[test]
+    fn test_dividend_yield_on() {
+        let dividend = DividendEstimate {
+            amount: PerShare(Money::of_minor(USD, 50)),
+            fiscal_period: FinancialPeriod::Quarter {
+                quarter: 1,
+                year: 2019,
+            },
+            ex_date: Date::from_ymd_opt(2019, 2, 8).unwrap(),
+        };
+        assert_eq!(
+            dividend.yield_on(PerShare(Money::of_major(USD, 50)), 4),
+            0.04
+        );
+        assert_eq!(
+            dividend.yield_on(PerShare(Money::of_major(USD, 50)), 1),
+            0.01
+        );
+        assert_eq!(dividend.yield_on(PerShare(Money::of_major(USD, 0)), 4), 0.0);
+        assert_eq!(
+            dividend.yield_on(PerShare(Money::of_major(EUR, 50)), 4),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_validate_all() {
+        assert_eq!(target(120, 80, 100).validate_all(), Ok(()));
+        assert_eq!(
+            target(80, 120, 130).validate_all(),
+            Err(vec![
+                ValidationError::LowAboveHigh,
+                ValidationError::AverageOutOfRange
+            ])
+        );
+        let mixed = PriceTarget {
+            low: PerShare(Money::of_major(EUR, -80)),
+            ..target(120, 80, 100)
+        };
+        assert_eq!(
+            mixed.validate_all(),
+            Err(vec![
+                ValidationError::CurrencyMismatch,
+                ValidationError::NegativeValue
+            ])
+        );
+
+        assert_eq!(eps(1, 100).validate_all(), Ok(()));
+        let mut invalid = eps(1, 100);
+        invalid.number_of_estimates = 0;
+        invalid.next_report_date = Date::from_ymd_opt(2019, 1, 1).unwrap();
+        assert_eq!(
+            invalid.validate_all(),
+            Err(vec![
+                ValidationError::NoEstimates,
+                ValidationError::ReportBeforeFiscalEnd
+            ])
+        );
+        assert_eq!(invalid.validate(), Err(ValidationError::NoEstimates));
+
+        let ranged = |high, low| {
+            eps(1, 100).with_estimate_range(
+                PerShare(Money::of_minor(USD, high)),
+                PerShare(Money::of_minor(USD, low)),
+            )
+        };
+        assert_eq!(ranged(120, 90).validate(), Ok(()));
+        assert_eq!(ranged(100, 100).validate(), Ok(()));
+        assert_eq!(
+            ranged(90, 80).validate(),
+            Err(ValidationError::ConsensusOutOfRange)
+        );
+        assert_eq!(
+            ranged(90, 120).validate_all(),
+            Err(vec![
+                ValidationError::LowAboveHigh,
+                ValidationError::ConsensusOutOfRange
+            ])
+        );
+        let mut high_only = eps(1, 100);
+        high_only.high_estimate = Some(PerShare(Money::of_minor(USD, 95)));
+        assert_eq!(
+            high_only.validate(),
+            Err(ValidationError::ConsensusOutOfRange)
+        );
+        high_only.high_estimate = Some(PerShare(Money::of_minor(EUR, 120)));
+        assert_eq!(high_only.validate(), Err(ValidationError::CurrencyMismatch));
+    }
+
+    #[test]
+    fn test_rating_codes() {
+        let all = [
+            RatingType::Buy,
+            RatingType::Outperform,
+            RatingType::Hold,
+            RatingType::Underperform,
+            RatingType::Sell,
+        ];
+        for scale in &[
+            CodeScale::OneIsBuy,
+            CodeScale::OneIsSell,
+            CodeScale::ZeroIsBuy,
+        ] {
+            for rating in &all {
+                assert_eq!(
+                    RatingType::from_code(rating.to_code(*scale), *scale),
+                    Some(*rating)
+                );
+            }
+        }
+        assert_eq!(RatingType::Buy.to_code(CodeScale::OneIsBuy), 1);
+        assert_eq!(RatingType::Buy.to_code(CodeScale::OneIsSell), 5);
+        assert_eq!(RatingType::Buy.to_code(CodeScale::ZeroIsBuy), 0);
+        assert_eq!(
+            RatingType::from_code(1, CodeScale::OneIsSell),
+            Some(RatingType::Sell)
+        );
+
+        assert_eq!(RatingType::from_code(0, CodeScale::OneIsBuy), None);
+        assert_eq!(RatingType::from_code(6, CodeScale::OneIsBuy), None);
+        assert_eq!(RatingType::from_code(0, CodeScale::OneIsSell), None);
+        assert_eq!(RatingType::from_code(6, CodeScale::OneIsSell), None);
+        assert_eq!(RatingType::from_code(5, CodeScale::ZeroIsBuy), None);
+    }
+
+    #[test]
+    fn test_projected_target() {
+        let snapshot = |month, high, low, average| {
+            Snapshot::new(datetime(2019, month, 1), target(high, low, average))
+        };
+        assert!(projected_target(&[snapshot(1, 120, 80, 100)], 30).is_none());
+
+        // March 1st to April 1st is 31 days, a rise of $1 a day.
+        let history = [snapshot(4, 165, 110, 131), snapshot(3, 120, 80, 100)];
+        let projected = projected_target(&history, 31).unwrap();
+        assert_eq!(projected.average, PerShare(Money::of_major(USD, 162)));
+        assert!(projected.validate().is_ok());
+        assert!(projected.high > PerShare(Money::of_major(USD, 165)));
+
+        let falling = [snapshot(3, 120, 80, 100), snapshot(4, 60, 20, 40)];
+        let projected = projected_target(&falling, 365).unwrap();
+        assert_eq!(projected.average, PerShare(Money::of_major(USD, 0)));
+        assert!(projected.validate().is_ok());
+
+        let same_day = [snapshot(3, 120, 80, 100), snapshot(3, 120, 80, 110)];
+        assert!(projected_target(&same_day, 30).is_none());
+    }
+
+    #[test]
+    fn test_scaled_average_by_firm() {
+        let estimate = |firm: &str, rating| AnalystEstimate {
+            firm: firm.to_string(),
+            target: None,
+            rating,
+            eps: None,
+            date: Date::from_ymd_opt(2019, 6, 1).unwrap(),
+        };
+        let estimates = [
+            estimate("Big", Some(RatingType::Buy)),
+            estimate("Small", Some(RatingType::Sell)),
+            estimate("Quiet", None),
+        ];
+        let mut weights = HashMap::new();
+        assert_eq!(scaled_average_by_firm(&estimates, &weights), Some(3.0));
+        weights.insert("Big".to_string(), 3.0);
+        assert_eq!(scaled_average_by_firm(&estimates, &weights), Some(2.0));
+        weights.insert("Big".to_string(), 0.0);
+        weights.insert("Small".to_string(), 0.0);
+        assert_eq!(scaled_average_by_firm(&estimates, &weights), None);
+        assert_eq!(scaled_average_by_firm(&[], &HashMap::new()), None);
+    }
+
+    #[test]
+    fn test_detect_rating_changes() {
+        let period = |month, buy, sell| {
+            Bounded::new(
+                datetime(2019, month, 1),
+                datetime(2019, month, 28),
+                ratings(buy, 0, sell),
+            )
+        };
+        // averages: 3.0, 1.8, (empty), 2.0, 5.0
+        let history = [
+            period(2, 4, 1),
+            period(1, 1, 1),
+            period(3, 0, 0),
+            period(4, 3, 1),
+            period(5, 0, 1),
+        ];
+        let changes = detect_rating_changes(&history, 0.5);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].date, Date::from_ymd_opt(2019, 2, 1).unwrap());
+        assert_eq!(changes[0].direction, Direction::Upgrade);
+        assert!((changes[0].magnitude - 1.2).abs() < 1e-9);
+        assert_eq!(changes[1].date, Date::from_ymd_opt(2019, 5, 1).unwrap());
+        assert_eq!(changes[1].direction, Direction::Downgrade);
+        assert!((changes[1].magnitude - 3.0).abs() < 1e-9);
+
+        assert_eq!(detect_rating_changes(&history, 0.0).len(), 3);
+        assert!(detect_rating_changes(&[], 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_ratings_from_iterator() {
+        let mut collected: Ratings = vec![RatingType::Buy, RatingType::Hold, RatingType::Buy]
+            .into_iter()
+            .collect();
+        assert_eq!(collected.ratings.get(&RatingType::Buy), Some(&2));
+        assert_eq!(collected.ratings.get(&RatingType::Hold), Some(&1));
+        assert_eq!(collected.ratings.get(&RatingType::Sell), None);
+        assert_eq!(collected.scale_mark, None);
+
+        collected.extend([RatingType::Sell, RatingType::Buy]);
+        assert_eq!(collected, ratings(3, 1, 1));
+
+        let empty: Ratings = std::iter::empty().collect();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_smoothed_consensus() {
+        let period = |month, buy, sell| {
+            Bounded::new(
+                datetime(2019, month, 1),
+                datetime(2019, month, 28),
+                ratings(buy, 0, sell),
+            )
+        };
+        // averages: 1.0, (empty), 5.0, 3.0, 1.0
+        let history = [
+            period(3, 0, 1),
+            period(1, 1, 0),
+            period(2, 0, 0),
+            period(4, 1, 1),
+            period(5, 1, 0),
+        ];
+        let end = |month| Date::from_ymd_opt(2019, month, 28).unwrap();
+
+        let raw = smoothed_consensus(&history, 1);
+        assert_eq!(
+            raw,
+            vec![(end(1), 1.0), (end(3), 5.0), (end(4), 3.0), (end(5), 1.0)]
+        );
+        assert_eq!(
+            smoothed_consensus(&history, 2),
+            vec![(end(3), 3.0), (end(4), 4.0), (end(5), 2.0)]
+        );
+        assert_eq!(smoothed_consensus(&history, 10), vec![(end(5), 2.5)]);
+        assert!(smoothed_consensus(&history, 0).is_empty());
+        assert!(smoothed_consensus(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_accuracy_tracker() {
+        let day = |d| Date::from_ymd_opt(2019, 6, d).unwrap();
+        let mut tracker = AccuracyTracker::new();
+        tracker.record(target(120, 80, 100), day(1));
+        tracker.record(target(120, 100, 110), day(2));
+        tracker.record(target(150, 90, 120), day(3));
+        assert_eq!(tracker.evaluate(&HashMap::new()), AccuracyReport::default());
+
+        let mut realized = HashMap::new();
+        realized.insert(day(1), Money::of_major(USD, 90));
+        realized.insert(day(2), Money::of_major(USD, 88));
+        let report = tracker.evaluate(&realized);
+        assert_eq!(report.count, 2);
+        assert!((report.mae - (10.0 / 90.0 + 22.0 / 88.0) / 2.0).abs() < 1e-9);
+        assert!((report.hit_rate - 0.5).abs() < 1e-9);
+        assert_eq!(tracker.pending(), 1);
+
+        realized.insert(day(3), Money::of_major(USD, 0));
+        assert_eq!(tracker.evaluate(&realized).count, 2);
+        assert_eq!(tracker.pending(), 0);
+    }
+
+    #[test]
+    fn test_implied_growth() {
+        let price = PerShare(Money::of_major(USD, 100));
+        let expected = 5.0 / 105.0;
+        let quarterly = eps(1, 125);
+        let growth = implied_growth(&quarterly, price, 0.1).unwrap();
+        assert!((growth - expected).abs() < 1e-9);
+        // the price is recovered from the implied growth
+        let annual = 5.0;
+        assert!((annual * (1.0 + growth) / (0.1 - growth) - 100.0).abs() < 1e-9);
+
+        let mut annual_eps = eps(1, 500);
+        annual_eps.fiscal_period = FinancialPeriod::Year { year: 2019 };
+        assert!((implied_growth(&annual_eps, price, 0.1).unwrap() - expected).abs() < 1e-9);
+
+        assert_eq!(implied_growth(&eps(1, 0), price, 0.1), None);
+        assert_eq!(implied_growth(&eps(1, -125), price, 0.1), None);
+        assert_eq!(
+            implied_growth(&quarterly, PerShare(Money::of_major(USD, 0)), 0.1),
+            None
+        );
+        assert_eq!(implied_growth(&quarterly, price, f64::NAN), None);
+    }
+
+    #[test]
+    fn test_revision_breadth() {
+        let prev = vec![eps(1, 100), eps(2, 100), eps(3, 100)];
+        let mut unmatched = eps(1, 50);
+        unmatched.fiscal_period = FinancialPeriod::Quarter {
+            quarter: 1,
+            year: 2020,
+        };
+        let mut curr = vec![eps(1, 110), eps(2, 90), eps(3, 120), unmatched];
+        assert!((revision_breadth(&prev, &curr) - 1.0 / 3.0).abs() < 1e-9);
+        curr[1].consensus = PerShare(Money::of_minor(USD, 100));
+        assert!((revision_breadth(&prev, &curr) - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(revision_breadth(&prev, &curr[3..]), 0.0);
+        assert_eq!(revision_breadth(&[], &[]), 0.0);
+    }
+
+    #[test]
+    fn test_current_ratings() {
+        let history = [
+            Bounded::new(
+                datetime(2019, 1, 1),
+                datetime(2019, 3, 31),
+                ratings(1, 0, 0),
+            ),
+            Bounded::new(
+                datetime(2019, 3, 1),
+                datetime(2019, 4, 30),
+                ratings(0, 1, 0),
+            ),
+        ];
+        let day = |month, day| Date::from_ymd_opt(2019, month, day).unwrap();
+        assert_eq!(current_ratings(&history, day(2, 1)), Some(&history[0].data));
+        assert_eq!(
+            current_ratings(&history, day(3, 15)),
+            Some(&history[1].data)
+        );
+        assert_eq!(
+            current_ratings(&history, day(4, 30)),
+            Some(&history[1].data)
+        );
+        assert_eq!(current_ratings(&history, day(5, 1)), None);
+        assert_eq!(current_ratings(&[], day(5, 1)), None);
+    }
+
+    #[test]
+    fn test_linreg_dated() {
+        let day = |d| Date::from_ymd_opt(2019, 6, d).unwrap();
+        let (slope, intercept) =
+            linreg_dated(&[(day(11), 12.0), (day(1), 10.0), (day(21), 14.0)]).unwrap();
+        assert!((slope - 0.2).abs() < 1e-9);
+        assert!((intercept - 12.0).abs() < 1e-9);
+
+        assert_eq!(linreg_dated(&[(day(1), 10.0)]), None);
+        assert_eq!(linreg_dated(&[(day(1), 10.0), (day(1), 12.0)]), None);
+        assert_eq!(linreg_dated(&[]), None);
+    }
+
+    #[test]
+    fn test_consistency_score() {
+        let current = PerShare(Money::of_major(USD, 100));
+        let buy = ratings(10, 0, 0);
+        let sell = ratings(0, 0, 10);
+        let hold = ratings(0, 10, 0);
+        let up = target(150, 110, 125);
+        let flat = target(110, 90, 100);
+
+        assert!((consistency_score(&buy, &up, current) - 1.0).abs() < 1e-9);
+        assert!(consistency_score(&sell, &up, current).abs() < 1e-9);
+        assert!((consistency_score(&sell, &flat, current) - 0.5).abs() < 1e-9);
+        assert!((consistency_score(&hold, &flat, current) - 1.0).abs() < 1e-9);
+        assert!((consistency_score(&hold, &up, current) - 0.5).abs() < 1e-9);
+        assert_eq!(consistency_score(&ratings(0, 0, 0), &up, current), 1.0);
+        assert_eq!(
+            consistency_score(&buy, &up, PerShare(Money::of_major(USD, 0))),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_forward_ttm_eps() {
+        let quarter = |quarter, year| FinancialPeriod::Quarter { quarter, year };
+        let mut next_year = eps(1, 150);
+        next_year.fiscal_period = quarter(1, 2020);
+        let mut revised = eps(3, 90);
+        revised.published = Date::from_ymd_opt(2019, 8, 1).unwrap();
+        let mut annual = eps(1, 1000);
+        annual.fiscal_period = FinancialPeriod::Year { year: 2019 };
+        let mut fourth = eps(3, 130);
+        fourth.fiscal_period = quarter(4, 2019);
+        let series = vec![eps(3, 120), next_year, eps(2, 110), revised, fourth, annual];
+
+        assert_eq!(
+            forward_ttm_eps(&series, quarter(2, 2019)),
+            Some(PerShare(Money::of_minor(USD, 110 + 90 + 130 + 150)))
+        );
+        assert_eq!(forward_ttm_eps(&series, quarter(3, 2019)), None);
+        assert_eq!(
+            forward_ttm_eps(&series, FinancialPeriod::Year { year: 2019 }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_dedup_eps() {
+        let mut more = eps(1, 100);
+        more.number_of_estimates = 8;
+        more.published = Date::from_ymd_opt(2019, 2, 1).unwrap();
+        let mut fewer = eps(1, 100);
+        fewer.number_of_estimates = 3;
+        let series = vec![eps(1, 100), eps(2, 100), more.clone(), eps(1, 101), fewer];
+
+        let unique = dedup_eps(series);
+        assert_eq!(unique, vec![more, eps(2, 100), eps(1, 101)]);
+        assert!(dedup_eps(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_adjust_target_for_actions() {
+        let split = |day, ratio| Split {
+            date: Date::from_ymd_opt(2019, 6, day).unwrap(),
+            ratio,
+        };
+        let original = target(300, 150, 240);
+        assert_eq!(original.split_adjust(2.0), target(150, 75, 120));
+        assert_eq!(original.split_adjust(0.0), original);
+        assert_eq!(original.split_adjust(f64::NAN), original);
+        assert_eq!(original.split_adjust(1e-9), original);
+
+        let actions = [split(20, 0.5), split(1, 3.0)];
+        assert_eq!(
+            adjust_target_for_actions(&original, &actions),
+            target(200, 100, 160)
+        );
+        assert_eq!(adjust_target_for_actions(&original, &[]), original);
+    }
+
+    #[test]
+    fn test_estimate_cv() {
+        let ranged = eps(1, 200).with_estimate_range(
+            PerShare(Money::of_minor(USD, 260)),
+            PerShare(Money::of_minor(USD, 180)),
+        );
+        assert!((ranged.estimate_cv().unwrap() - 0.1).abs() < 1e-9);
+        let mut loss = ranged.clone();
+        loss.consensus = PerShare(Money::of_minor(USD, -200));
+        assert!((loss.estimate_cv().unwrap() - 0.1).abs() < 1e-9);
+
+        assert_eq!(eps(1, 200).estimate_cv(), None);
+        assert_eq!(
+            eps(1, 0)
+                .with_estimate_range(
+                    PerShare(Money::of_minor(USD, 10)),
+                    PerShare(Money::of_minor(USD, -10))
+                )
+                .estimate_cv(),
+            None
+        );
+        assert_eq!(eps(1, 200).fingerprint(), eps(1, 200).fingerprint());
+        assert_ne!(ranged.fingerprint(), eps(1, 200).fingerprint());
+        let mut high_only = eps(1, 200);
+        high_only.high_estimate = Some(PerShare(Money::of_minor(USD, 260)));
+        let mut low_only = eps(1, 200);
+        low_only.low_estimate = Some(PerShare(Money::of_minor(USD, 260)));
+        assert_ne!(high_only.fingerprint(), low_only.fingerprint());
+        assert_ne!(high_only.fingerprint(), ranged.fingerprint());
+    }
+
+    struct Flaky {
+        calls: AtomicU32,
+        failures: u32,
+    }
+
+    impl Flaky {
+        fn new(failures: u32) -> Self {
+            Flaky {
+                calls: AtomicU32::new(0),
+                failures,
+            }
+        }
+
+        fn calls(&self) -> u32 {
+            self.calls.load(AtomicOrdering::SeqCst)
+        }
+    }
+
+    impl AnalystRecommendations for Flaky {
+        fn target_price(&self, for_symbol: Symbol) -> RequestResult<Snapshot<PriceTarget>> {
+            let call = self.calls.fetch_add(1, AtomicOrdering::SeqCst);
+            if for_symbol == "BAD" {
+                Err(RequestError::BadSymbolError(for_symbol))
+            } else if call < self.failures {
+                Err(RequestError::CommunicationError)
+            } else {
+                Ok(Snapshot::new(datetime(2019, 6, 1), target(120, 80, 100)))
+            }
+        }
+
+        fn consensus_rating(&self, _for_symbol: Symbol) -> RequestResult<Vec<Bounded<Ratings>>> {
+            Err(RequestError::Unsupported)
+        }
+
+        fn consensus_eps(&self, _for_symbol: Symbol) -> RequestResult<Vec<EPSConsensus>> {
+            Err(RequestError::Unsupported)
+        }
+    }
+
+    #[test]
+    fn test_wrapped_cache_and_retry() {
+        let policy = ProviderPolicy {
+            ttl: Some(Duration::from_secs(60)),
+            max_attempts: 3,
+            ..Default::default()
+        };
+        let wrapped = Wrapped::new(Flaky::new(2), policy.clone());
+        assert!(wrapped.target_price("AAPL".to_string()).is_ok());
+        assert_eq!(wrapped.inner().calls(), 3);
+        assert!(wrapped.target_price("AAPL".to_string()).is_ok());
+        assert_eq!(wrapped.inner().calls(), 3);
+        wrapped.clear_cache();
+        assert!(wrapped.target_price("AAPL".to_string()).is_ok());
+        assert_eq!(wrapped.inner().calls(), 4);
+
+        // errors are neither retried, unless transient, nor cached
+        assert!(wrapped.target_price("BAD".to_string()).is_err());
+        assert!(wrapped.target_price("BAD".to_string()).is_err());
+        assert_eq!(wrapped.inner().calls(), 6);
+
+        let wrapped = Wrapped::new(Flaky::new(5), policy);
+        match wrapped.target_price("AAPL".to_string()) {
+            Err(RequestError::CommunicationError) => (),
+            _ => panic!("expected a CommunicationError"),
+        }
+        assert_eq!(wrapped.inner().calls(), 3);
+
+        let wrapped = Wrapped::new(Flaky::new(1), ProviderPolicy::default());
+        assert!(wrapped.target_price("AAPL".to_string()).is_err());
+        assert!(wrapped.target_price("AAPL".to_string()).is_ok());
+        assert_eq!(wrapped.inner().calls(), 2);
+    }
+
+    #[test]
+    fn test_wrapped_rate_limit() {
+        let interval = Duration::from_millis(20);
+        let policy = ProviderPolicy {
+            min_interval: Some(interval),
+            ..Default::default()
+        };
+        let wrapped = Wrapped::new(Flaky::new(0), policy);
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(wrapped.target_price("AAPL".to_string()).is_ok());
+        }
+        assert!(start.elapsed() >= interval * 2);
+    }
+
+    #[test]
+    fn test_verdict() {
+        let current = PerShare(Money::of_major(USD, 100));
+        let bullish = ratings(10, 2, 0);
+        let bearish = ratings(0, 2, 10);
+        let neutral = ratings(0, 10, 0);
+        let big_up = target(150, 110, 130);
+        let small_up = target(120, 90, 105);
+        let big_down = target(90, 60, 75);
+
+        assert_eq!(
+            verdict(Some(&bullish), &big_up, current),
+            Verdict::StrongBuy
+        );
+        assert_eq!(verdict(Some(&bullish), &small_up, current), Verdict::Buy);
+        assert_eq!(verdict(Some(&bullish), &big_down, current), Verdict::Hold);
+        assert_eq!(
+            verdict(Some(&bearish), &big_down, current),
+            Verdict::StrongSell
+        );
+        assert_eq!(verdict(Some(&bearish), &small_up, current), Verdict::Hold);
+        assert_eq!(verdict(Some(&neutral), &big_up, current), Verdict::Buy);
+        assert_eq!(verdict(Some(&neutral), &small_up, current), Verdict::Hold);
+        assert_eq!(verdict(None, &big_up, current), Verdict::StrongBuy);
+        assert_eq!(verdict(None, &small_up, current), Verdict::Buy);
+        assert_eq!(verdict(None, &big_down, current), Verdict::StrongSell);
+        assert_eq!(
+            verdict(Some(&ratings(0, 0, 0)), &target(102, 98, 100), current),
+            Verdict::Hold
+        );
+        assert_eq!(
+            verdict(Some(&bullish), &big_up, PerShare(Money::of_major(USD, 0))),
+            Verdict::Buy
+        );
+    }
+
+    #[test]
+    fn test_infer_average() {
+        let inferred = PriceTarget::infer_average(
+            PerShare(Money::of_major(USD, 120)),
+            PerShare(Money::of_minor(USD, 8001)),
+            7,
+        );
+        assert_eq!(inferred.average, PerShare(Money::of_minor(USD, 10000)));
+        assert_eq!(inferred.number_of_analysts, 7);
+        assert_eq!(inferred.validate(), Ok(()));
+
+        let single = PriceTarget::infer_average(
+            PerShare(Money::of_major(USD, 50)),
+            PerShare(Money::of_major(USD, 50)),
+            1,
+        );
+        assert_eq!(single.average, PerShare(Money::of_major(USD, 50)));
+        assert_eq!(single.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_coverage_stats() {
+        let symbols: Symbols = ["AAPL", "BAD", "NONE"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let stats = coverage_stats(&BySymbol, &symbols).unwrap();
+        assert_eq!(
+            stats,
+            CoverageStats {
+                with_targets: 0,
+                with_ratings: 1,
+                with_eps: 0,
+                uncovered: 2,
+                avg_analysts: 0.0,
+            }
+        );
+
+        let full = provider(Some(target(120, 80, 100)), vec![ratings(1, 1, 1)]);
+        let stats = coverage_stats(&full, &symbols).unwrap();
+        assert_eq!(stats.with_targets, 3);
+        assert_eq!(stats.with_eps, 3);
+        assert_eq!(stats.uncovered, 0);
+        assert!((stats.avg_analysts - 10.0).abs() < 1e-9);
+
+        assert!(matches!(
+            coverage_stats(&Flaky::new(10), &symbols),
+            Err(RequestError::CommunicationError)
+        ));
+    }
+
+    #[test]
+    fn test_calibrate_scales() {
+        let sample = |symbol: &str, value| (symbol.to_string(), value);
+        // b uses a 0-4 scale where a uses 1-5, and b reverses the direction
+        let a = [
+            sample("AAPL", 2.0),
+            sample("MSFT", 3.0),
+            sample("IBM", 4.0),
+            sample("AAPL", 9.0),
+            sample("ONLY_A", 1.0),
+        ];
+        let b = [
+            sample("IBM", 1.0),
+            sample("MSFT", 2.0),
+            sample("AAPL", 3.0),
+            sample("ONLY_B", 0.0),
+        ];
+        let mapping = calibrate_scales(&a, &b).unwrap();
+        assert!((mapping.slope + 1.0).abs() < 1e-9);
+        assert!((mapping.intercept - 5.0).abs() < 1e-9);
+        assert!((mapping.apply(0.0) - 5.0).abs() < 1e-9);
+
+        assert_eq!(calibrate_scales(&a[..1], &b), None);
+        assert_eq!(calibrate_scales(&a, &[]), None);
+        assert_eq!(
+            calibrate_scales(&a, &[sample("AAPL", 2.0), sample("MSFT", 2.0)]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_consensus_type() {
+        let outperform = Some(RatingType::Outperform);
+        assert_eq!(ratings(4, 1, 0).consensus_type(), Some(RatingType::Buy));
+        assert_eq!(ratings(1, 1, 0).consensus_type(), outperform);
+        assert_eq!(ratings(3, 1, 0).consensus_type(), outperform);
+        assert_eq!(ratings(1, 0, 1).consensus_type(), Some(RatingType::Hold));
+        assert_eq!(ratings(0, 0, 2).consensus_type(), Some(RatingType::Sell));
+        assert_eq!(ratings(0, 0, 0).consensus_type(), None);
+    }
+
+    #[test]
+    fn test_analyst_data_builder() {
+        let targets = AnalystData {
+            target: Some(Snapshot::new(datetime(2019, 6, 1), target(120, 80, 100))),
+            ratings: Vec::new(),
+            eps: Vec::new(),
+        };
+        let ratings = vec![
+            Bounded::new(
+                datetime(2019, 1, 1),
+                datetime(2019, 1, 31),
+                ratings(3, 2, 1),
+            ),
+            Bounded::new(
+                datetime(2019, 2, 1),
+                datetime(2019, 2, 28),
+                ratings(4, 1, 1),
+            ),
+        ];
+        let eps = vec![eps(1, 100), eps(2, 110)];
+
+        let mut builder = AnalystData::builder();
+        if let Some(target) = targets.target.clone() {
+            builder = builder.with_target(target);
+        }
+        builder = builder.with_target(Snapshot::new(datetime(2019, 5, 1), target(90, 70, 80)));
+        for period in ratings.clone() {
+            builder = builder.add_rating_period(period);
+        }
+        for e in eps.clone() {
+            builder = builder.add_eps(e);
+        }
+        let data = builder.build();
+        assert_eq!(data.target, targets.target);
+        assert_eq!(data.ratings, ratings);
+        assert_eq!(data.eps, eps);
+
+        let newer = Snapshot::new(datetime(2019, 7, 1), target(90, 70, 80));
+        let data = AnalystData::builder()
+            .with_target(Snapshot::new(datetime(2019, 6, 1), target(120, 80, 100)))
+            .with_target(newer.clone())
+            .build();
+        assert_eq!(data.target, Some(newer));
+        assert_eq!(AnalystData::builder().build(), AnalystData::default());
+    }
+
+    struct RatedBy(HashMap<Symbol, Ratings>);
+
+    impl AnalystRecommendations for RatedBy {
+        fn target_price(&self, _for_symbol: Symbol) -> RequestResult<Snapshot<PriceTarget>> {
+            Err(RequestError::Unsupported)
+        }
+
+        fn consensus_rating(&self, for_symbol: Symbol) -> RequestResult<Vec<Bounded<Ratings>>> {
+            match self.0.get(&for_symbol) {
+                None => Err(RequestError::BadSymbolError(for_symbol)),
+                Some(ratings) => Ok(vec![Bounded::new(
+                    datetime(2019, 1, 1),
+                    datetime(2019, 1, 31),
+                    ratings.clone(),
+                )]),
+            }
+        }
+
+        fn consensus_eps(&self, _for_symbol: Symbol) -> RequestResult<Vec<EPSConsensus>> {
+            Err(RequestError::Unsupported)
+        }
+    }
+
+    #[test]
+    fn test_crowding_index() {
+        let mut rated = HashMap::new();
+        rated.insert("AAPL".to_string(), ratings(1, 0, 0));
+        rated.insert("P0A".to_string(), ratings(1, 0, 0));
+        let crowding = |rated: &HashMap<Symbol, Ratings>| {
+            crowding_index(&ThreePages, &RatedBy(rated.clone()), "AAPL".to_string()).unwrap()
+        };
+        assert_eq!(crowding(&rated), None);
+
+        rated.insert("P1B".to_string(), ratings(1, 0, 0));
+        assert_eq!(crowding(&rated), Some(1.0));
+
+        // peers average 3.0, a distance of 2.0 from the symbol's 1.0
+        rated.insert("P2A".to_string(), ratings(0, 0, 1));
+        rated.insert("P2B".to_string(), ratings(0, 0, 1));
+        assert_eq!(crowding(&rated), Some(0.5));
+
+        rated.remove("AAPL");
+        assert_eq!(crowding(&rated), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ratings_history_columns() {
+        let mut history = provider(None, vec![ratings(5, 3, 1), ratings(0, 2, 0)])
+            .0
+            .ratings;
+        history[0].data.scale_mark = Some(1.5);
+        history[1] = history[1].clone().with_source("IEX");
+        history[1].data.ratings.remove(&RatingType::Sell);
+
+        let columns = ratings_history_to_columns(&history);
+        assert_eq!(columns.buy, vec![Some(5), Some(0)]);
+        assert_eq!(columns.sell, vec![Some(1), None]);
+        assert_eq!(columns.outperform, vec![None, None]);
+        assert_eq!(ratings_history_from_columns(&columns).unwrap(), history);
+
+        let json = serde_json::to_string(&columns).unwrap();
+        let parsed: RatingsColumns = serde_json::from_str(&json).unwrap();
+        assert_eq!(ratings_history_from_columns(&parsed).unwrap(), history);
+
+        let mut ragged = columns;
+        ragged.hold.pop();
+        assert_eq!(ratings_history_from_columns(&ragged), None);
+    }
+
+    struct FixedRate(f64);
+
+    impl ExchangeRates for FixedRate {
+        fn rate(&self, _from: Currency, _to: Currency, _on: Date) -> RequestResult<f64> {
+            Ok(self.0)
+        }
+    }
+
+    #[test]
+    fn test_eps_normalize() {
+        let usd = |minor| PerShare(Money::of_minor(USD, minor));
+        let eur = |minor| PerShare(Money::of_minor(EUR, minor));
+        let ranged = eps(1, 150).with_estimate_range(usd(200), usd(100));
+        assert_eq!(ranged.normalize(USD, 1.0, &FixedRate(3.0)).unwrap(), ranged);
+
+        let mut euros = eps(1, 150).with_estimate_range(eur(200), eur(100));
+        euros.consensus = eur(150);
+        let normalized = euros.normalize(USD, 2.0, &FixedRate(3.0)).unwrap();
+        assert_eq!(normalized.consensus, usd(225));
+        assert_eq!(normalized.high_estimate, Some(usd(300)));
+        assert_eq!(normalized.low_estimate, Some(usd(150)));
+        assert_eq!(normalized.fiscal_period, euros.fiscal_period);
+
+        for adr_ratio in [0.0, -1.0, f64::NAN] {
+            assert!(matches!(
+                euros.normalize(USD, adr_ratio, &FixedRate(3.0)),
+                Err(RequestError::BadRequestError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let first = eps(1, 100);
+        let report = first.next_report_date;
+        assert!(!first.is_stale(report.pred_opt().unwrap()));
+        assert!(!first.is_stale(report));
+        assert!(first.is_stale(report.succ_opt().unwrap()));
+
+        let series = vec![eps(1, 100), eps(2, 110)];
+        assert_eq!(prune_stale(series.clone(), report), series);
+        assert_eq!(
+            prune_stale(series.clone(), report.succ_opt().unwrap()),
+            vec![eps(2, 110)]
+        );
+        let last = series[1].next_report_date;
+        assert_eq!(prune_stale(series.clone(), last), vec![eps(2, 110)]);
+        assert!(prune_stale(series, last.succ_opt().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_blend() {
+        assert_eq!(blend(&[]), None);
+        let mut none = target(120, 80, 100);
+        none.number_of_analysts = 0;
+        assert_eq!(blend(&[none.clone()]), None);
+
+        let mut wide = target(150, 90, 120);
+        wide.number_of_analysts = 30;
+        let blended = blend(&[none, target(110, 70, 80), wide]).unwrap();
+        assert_eq!(blended, {
+            let mut expected = target(140, 85, 110);
+            expected.number_of_analysts = 40;
+            expected
+        });
+
+        let mut many = target(120, 80, 100);
+        many.number_of_analysts = Counter::MAX;
+        let blended = blend(&[many.clone(), many]).unwrap();
+        assert_eq!(blended.number_of_analysts, Counter::MAX);
+        assert_eq!(blended.average, PerShare(Money::of_major(USD, 100)));
+
+        let mut euro = target(120, 80, 100);
+        euro.low = PerShare(Money::of_major(EUR, 80));
+        assert_eq!(blend(&[target(120, 80, 100), euro]), None);
+    }
+
+    #[test]
+    fn test_blend_in() {
+        let today = Date::from_ymd_opt(2019, 6, 1).unwrap();
+        let euro = PriceTarget {
+            high: PerShare(Money::of_major(EUR, 60)),
+            low: PerShare(Money::of_major(EUR, 40)),
+            average: PerShare(Money::of_major(EUR, 50)),
+            number_of_analysts: 10,
+        };
+        assert_eq!(
+            euro.convert(USD, &FixedRate(2.0), today).unwrap(),
+            target(120, 80, 100)
+        );
+        assert_eq!(euro.convert(EUR, &FixedRate(2.0), today).unwrap(), euro);
+
+        let blended = blend_in(
+            &[euro.clone(), target(140, 100, 120)],
+            USD,
+            &FixedRate(2.0),
+            today,
+        );
+        assert_eq!(
+            blended.unwrap(),
+            Some({
+                let mut expected = target(130, 90, 110);
+                expected.number_of_analysts = 20;
+                expected
+            })
+        );
+        assert_eq!(
+            blend(&[euro, target(140, 100, 120)]),
+            None,
+            "mixed currencies are only blended by blend_in"
+        );
+        assert_eq!(blend_in(&[], USD, &FixedRate(2.0), today).unwrap(), None);
+    }
+
+    #[test]
+    fn test_blend_with_band() {
+        let usd = |value| PerShare(Money::of_major(USD, value));
+        assert_eq!(blend_with_band(&[]), None);
+        assert_eq!(
+            blend_with_band(&[target(120, 80, 100)]),
+            Some((usd(100), usd(100), usd(100)))
+        );
+        assert_eq!(
+            blend_with_band(&[target(120, 80, 90), target(130, 90, 110)]),
+            Some((usd(90), usd(100), usd(110)))
+        );
+
+        let mut narrow = target(120, 80, 90);
+        narrow.number_of_analysts = 30;
+        let (low, central, high) = blend_with_band(&[narrow, target(130, 90, 110)]).unwrap();
+        assert_eq!(central, usd(95));
+        assert!(low > usd(86) && high < usd(104));
+    }
+
+    #[test]
+    fn test_next_catalyst() {
+        let data = provider(None, vec![]).0;
+        let date = |month, day| Date::from_ymd_opt(2019, month, day).unwrap();
+        let dividend = |ex_date| DividendEstimate {
+            amount: PerShare(Money::of_minor(USD, 50)),
+            fiscal_period: FinancialPeriod::Quarter {
+                quarter: 2,
+                year: 2019,
+            },
+            ex_date,
+        };
+        // the EPS report dates are 2019-04-28 and 2019-07-28
+        assert_eq!(
+            next_catalyst(&data, &[], date(4, 1)),
+            Some((date(4, 28), CatalystKind::Earnings))
+        );
+        assert_eq!(
+            next_catalyst(&data, &[dividend(date(5, 15))], date(4, 29)),
+            Some((date(5, 15), CatalystKind::ExDividend))
+        );
+        assert_eq!(
+            next_catalyst(&data, &[dividend(date(7, 28))], date(7, 28)),
+            Some((date(7, 28), CatalystKind::Earnings))
+        );
+        assert_eq!(
+            next_catalyst(&data, &[dividend(date(5, 15))], date(8, 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_net_sentiment() {
+        assert_eq!(ratings(4, 0, 0).net_sentiment(), Some(1.0));
+        assert_eq!(ratings(0, 0, 4).net_sentiment(), Some(-1.0));
+        assert_eq!(ratings(0, 4, 0).net_sentiment(), Some(0.0));
+        assert_eq!(ratings(3, 4, 1).net_sentiment(), Some(0.25));
+        assert_eq!(ratings(0, 0, 0).net_sentiment(), None);
+    }
+
+    #[test]
+    fn test_latest_consensus_rating() {
+        let symbol = "AAPL".to_string();
+        let reco = provider(None, vec![ratings(1, 1, 1), ratings(4, 1, 0)]);
+        let latest = reco
+            .latest_consensus_rating(symbol.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.start_date, datetime(2019, 2, 1));
+        assert_eq!(latest.data, ratings(4, 1, 0));
+        assert_eq!(
+            provider(None, vec![])
+                .latest_consensus_rating(symbol)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            BySymbol.latest_consensus_rating("BAD".to_string()).unwrap(),
+            None
+        );
+        assert_eq!(
+            BySymbol.latest_consensus_rating("OTC".to_string()).unwrap(),
+            None
+        );
+        assert!(matches!(
+            BySymbol.latest_consensus_rating("DOWN".to_string()),
+            Err(RequestError::CommunicationError)
+        ));
+    }
+
+    struct Sectors(HashMap<Symbol, String>);
+
+    impl FetchCompanyInformation for Sectors {
+        fn about(&self, for_symbol: Symbol) -> RequestResult<About> {
+            Ok(About {
+                company_name: for_symbol.clone(),
+                web_site: String::new(),
+                employees: 0,
+                description: String::new(),
+                logo_url: None,
+                security: Security {
+                    symbol: for_symbol.clone(),
+                    market: "NASDAQ".to_string(),
+                    security_name: for_symbol.clone(),
+                    issue_type: None,
+                    sector: self.0.get(&for_symbol).cloned(),
+                },
+                tags: Vec::new(),
+            })
+        }
+
+        fn filings(
+            &self,
+            _for_symbol: Symbol,
+            _start_date: Option<Date>,
+            _form_type: Option<String>,
+        ) -> RequestResult<RegulatoryFilings> {
+            Err(RequestError::Unsupported)
+        }
+    }
+
+    #[test]
+    fn test_sector_consensus() {
+        let mut rated = HashMap::new();
+        rated.insert("AAPL".to_string(), ratings(3, 1, 0));
+        rated.insert("MSFT".to_string(), ratings(1, 1, 0));
+        rated.insert("XOM".to_string(), ratings(0, 1, 2));
+        rated.insert("BRK".to_string(), ratings(0, 2, 0));
+        let mut sectors = HashMap::new();
+        sectors.insert("AAPL".to_string(), "Technology".to_string());
+        sectors.insert("MSFT".to_string(), "Technology".to_string());
+        sectors.insert("XOM".to_string(), "Energy".to_string());
+        sectors.insert("NONE".to_string(), "Energy".to_string());
+        let symbols: Symbols = ["AAPL", "MSFT", "XOM", "BRK", "NONE"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let consensus = sector_consensus(&Sectors(sectors), &RatedBy(rated), &symbols).unwrap();
+        assert_eq!(consensus.len(), 3);
+        assert_eq!(consensus["Technology"], ratings(4, 2, 0));
+        assert_eq!(consensus["Energy"], ratings(0, 1, 2));
+        assert_eq!(consensus[UNCLASSIFIED_SECTOR], ratings(0, 2, 0));
+    }
+
+    #[test]
+    fn test_implied_forward_pe() {
+        let target = target(240, 160, 200);
+        let mut eps = eps(3, 1000);
+        eps.fiscal_period = FinancialPeriod::Year { year: 2019 };
+        assert_eq!(implied_forward_pe(&target, &eps), Some(20.0));
+        assert_eq!(implied_pe_range(&target, &eps), Some((16.0, 24.0)));
+
+        let mut loss = eps.clone();
+        loss.consensus = PerShare(Money::of_minor(USD, -100));
+        assert_eq!(implied_forward_pe(&target, &loss), None);
+        assert_eq!(implied_pe_range(&target, &loss), None);
+
+        let mut euros = eps;
+        euros.consensus = PerShare(Money::of_minor(EUR, 1000));
+        assert_eq!(implied_forward_pe(&target, &euros), None);
+        assert_eq!(implied_pe_range(&target, &euros), None);
+    }
+
+    #[test]
+    fn test_accuracy_weighted_target() {
+        assert_eq!(accuracy_weighted_target(&[]), None);
+        assert_eq!(
+            accuracy_weighted_target(&[(target(120, 80, 100), 0.0)]),
+            None
+        );
+
+        let blended = accuracy_weighted_target(&[
+            (target(120, 80, 100), 0.75),
+            (target(240, 160, 200), 0.25),
+            (target(1000, 1000, 1000), -1.0),
+        ])
+        .unwrap();
+        assert_eq!(blended.average, PerShare(Money::of_major(USD, 125)));
+        assert_eq!(blended.high, PerShare(Money::of_major(USD, 150)));
+        assert_eq!(blended.low, PerShare(Money::of_major(USD, 100)));
+        assert_eq!(blended.number_of_analysts, 20);
+
+        let mut many = target(120, 80, 100);
+        many.number_of_analysts = Counter::MAX;
+        let blended = accuracy_weighted_target(&[(many.clone(), 1.0), (many, 1.0)]).unwrap();
+        assert_eq!(blended.number_of_analysts, Counter::MAX);
+
+        let mut euros = target(120, 80, 100);
+        euros.average = PerShare(Money::of_major(EUR, 100));
+        assert_eq!(
+            accuracy_weighted_target(&[(target(120, 80, 100), 1.0), (euros, 1.0)]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_coverage_status() {
+        let covered =
+            |ratings: Vec<Ratings>| provider(None, ratings).consensus_rating("AAPL".to_string());
+        assert_eq!(
+            coverage_status_of(&covered(vec![ratings(1, 1, 1)])),
+            Some(CoverageStatus::CoveredWithData)
+        );
+        assert_eq!(
+            coverage_status_of(&covered(vec![])),
+            Some(CoverageStatus::CoveredNoData)
+        );
+        assert_eq!(
+            coverage_status(&covered(vec![]).ok()),
+            CoverageStatus::CoveredNoData
+        );
+
+        let uncovered = RatedBy(HashMap::new()).consensus_rating("AAPL".to_string());
+        assert_eq!(
+            coverage_status_of(&uncovered),
+            Some(CoverageStatus::NotCovered)
+        );
+        assert_eq!(
+            coverage_status::<Ratings>(&None),
+            CoverageStatus::NotCovered
+        );
+
+        let unsupported: RequestResult<Vec<Ratings>> = Err(RequestError::Unsupported);
+        assert_eq!(coverage_status_of(&unsupported), None);
+        let failed: RequestResult<Vec<Ratings>> = Err(RequestError::CommunicationError);
+        assert_eq!(coverage_status_of(&failed), None);
+    }
+
+    #[test]
+    fn test_rating_stability() {
+        let now = Date::from_ymd_opt(2019, 3, 31).unwrap();
+        let stable = provider(
+            None,
+            vec![ratings(4, 0, 0), ratings(4, 0, 0), ratings(4, 0, 0)],
+        );
+        assert_eq!(rating_stability(&stable.0.ratings, 90, now), Some(1.0));
+        assert_eq!(rating_stability(&stable.0.ratings, 20, now), None);
+
+        // averages of 1.0 and 5.0 are the most unstable possible
+        let flipping = provider(
+            None,
+            vec![ratings(0, 0, 0), ratings(1, 0, 0), ratings(0, 0, 1)],
+        );
+        assert_eq!(rating_stability(&flipping.0.ratings, 90, now), Some(0.0));
+
+        let settling = provider(
+            None,
+            vec![ratings(1, 1, 0), ratings(0, 1, 0), ratings(0, 1, 0)],
+        );
+        let stability = rating_stability(&settling.0.ratings, 90, now).unwrap();
+        assert!(stability > 0.9 && stability < 1.0);
+        assert_eq!(rating_stability(&settling.0.ratings, 40, now), Some(1.0));
+    }
+
+    #[test]
+    fn test_round_number_bias() {
+        let targets = [
+            Money::of_major(USD, 100),
+            Money::of_major(USD, 150),
+            Money::of_minor(USD, 12_250),
+            Money::of_major(EUR, 100),
+        ];
+        assert_eq!(round_number_bias(&targets, Money::of_major(USD, 50)), 0.5);
+        assert_eq!(round_number_bias(&targets, Money::of_major(USD, 10)), 0.5);
+        assert_eq!(round_number_bias(&targets, Money::of_minor(USD, 50)), 0.75);
+        assert_eq!(round_number_bias(&targets, Money::of_major(USD, 0)), 0.0);
+        assert_eq!(round_number_bias(&[], Money::of_major(USD, 5)), 0.0);
+    }
+
+    #[test]
+    fn test_rating_freshness() {
+        let history = provider(None, vec![ratings(1, 0, 0), ratings(1, 0, 0)])
+            .0
+            .ratings;
+        let date = |month, day| Date::from_ymd_opt(2019, month, day).unwrap();
+        assert_eq!(rating_freshness(&history, date(3, 10)), Some((10, 41)));
+        assert_eq!(rating_freshness(&history, date(2, 10)), Some((0, 13)));
+        assert_eq!(rating_freshness(&[], date(3, 10)), None);
+    }
+
+    #[test]
+    fn test_forward_fill_targets() {
+        let date = |day| Date::from_ymd_opt(2019, 6, day).unwrap();
+        let snapshot =
+            |day, average| Snapshot::new(datetime(2019, 6, day), target(200, 50, average));
+        let snapshots = [snapshot(5, 120), snapshot(2, 100), snapshot(5, 110)];
+
+        let series = forward_fill_targets(&snapshots, date(1), date(6));
+        let averages: Vec<(Date, i32)> = series
+            .iter()
+            .map(|(date, t)| (*date, t.average.0.major_part()))
+            .collect();
+        assert_eq!(
+            averages,
+            vec![
+                (date(2), 100),
+                (date(3), 100),
+                (date(4), 100),
+                (date(5), 110),
+                (date(6), 110)
+            ]
+        );
+        assert_eq!(forward_fill_targets(&snapshots, date(4), date(3)), vec![]);
+        assert_eq!(forward_fill_targets(&[], date(1), date(6)), vec![]);
+    }
+
+    #[test]
+    fn test_target_information_ratio() {
+        let current = PerShare(Money::of_major(USD, 80));
+        // an upside of 25% over a relative dispersion of 0.4
+        assert_eq!(
+            target(120, 80, 100).target_information_ratio(current),
+            Some(0.625)
+        );
+        assert_eq!(
+            target(100, 100, 100).target_information_ratio(current),
+            None
+        );
+        assert_eq!(target(20, 0, 0).target_information_ratio(current), None);
+        assert_eq!(
+            target(120, 80, 100).target_information_ratio(PerShare(Money::of_major(USD, 0))),
+            None
+        );
+    }
 }
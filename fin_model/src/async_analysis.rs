@@ -0,0 +1,280 @@
+/*!
+Provides an asynchronous form of the analysis _request traits_, and an adapter
+that exposes a synchronous provider through it.
+
+This module requires the `tokio` feature. The `SpawnBlocking` adapter runs each
+request of the wrapped provider on the Tokio blocking thread pool, using
+`tokio::task::spawn_blocking`, so that synchronous network calls do not block
+the async executor. Results, including errors, are returned unchanged.
+
+## Example
+
+```rust,ignore
+use fin_model::async_analysis::{AsyncRecommendations, SpawnBlocking};
+
+let provider = SpawnBlocking::new(IEXProvider::new()?);
+let target = provider.target_price("AAPL".to_string()).await?;
+```
+*/
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic;
+use std::sync::Arc;
+
+use crate::analysis::*;
+use crate::prelude::*;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// Wraps a synchronous `AnalystRecommendations` provider so that it can be
+/// used as an `AsyncRecommendations` provider. The `Provider` properties of the
+/// wrapped provider, such as `Provider::attribution_today` for stamping
+/// results, are available from `inner`.
+pub struct SpawnBlocking<P> {
+    provider: Arc<P>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Traits
+// ------------------------------------------------------------------------------------------------
+
+/// The asynchronous form of `AnalystRecommendations`, with a method for each
+/// of its methods. There are no default implementations; the `SpawnBlocking`
+/// adapter forwards each request to the wrapped provider, so that any of the
+/// provider's own implementations of the provided methods are used.
+pub trait AsyncRecommendations {
+    /// Return the target price recommendations for the symbol
+    fn target_price(
+        &self,
+        for_symbol: Symbol,
+    ) -> impl Future<Output = RequestResult<Snapshot<PriceTarget>>> + Send;
+
+    /// Return the consensus ratings for the symbol
+    fn consensus_rating(
+        &self,
+        for_symbol: Symbol,
+    ) -> impl Future<Output = RequestResult<Vec<Bounded<Ratings>>>> + Send;
+
+    /// Return the consensus earnings per share (EPS) for the symbol
+    fn consensus_eps(
+        &self,
+        for_symbol: Symbol,
+    ) -> impl Future<Output = RequestResult<Vec<EPSConsensus>>> + Send;
+
+    /// Return the consensus EPS for the symbol as known `as_of` the given
+    /// date, see `AnalystRecommendations::consensus_eps_at`.
+    fn consensus_eps_at(
+        &self,
+        for_symbol: Symbol,
+        as_of: Date,
+    ) -> impl Future<Output = RequestResult<Option<Vec<EPSConsensus>>>> + Send;
+
+    /// Return the individual analyst estimates for the symbol, see
+    /// `AnalystRecommendations::analyst_estimates`.
+    fn analyst_estimates(
+        &self,
+        for_symbol: Symbol,
+    ) -> impl Future<Output = RequestResult<Option<Vec<AnalystEstimate>>>> + Send;
+
+    /// Return the change in consensus rating for the symbol since the given
+    /// date, see `AnalystRecommendations::rating_change_since`.
+    fn rating_change_since(
+        &self,
+        for_symbol: Symbol,
+        since: Date,
+    ) -> impl Future<Output = RequestResult<Option<RatingChangeEvent>>> + Send;
+
+    /// Return the consensus ratings for a number of symbols, see
+    /// `AnalystRecommendations::consensus_rating_many`.
+    fn consensus_rating_many(
+        &self,
+        symbols: &[Symbol],
+    ) -> impl Future<Output = RequestResult<HashMap<Symbol, RequestResult<Vec<Bounded<Ratings>>>>>> + Send;
+
+    /// Return the most recent consensus ratings period for the symbol, see
+    /// `AnalystRecommendations::latest_consensus_rating`.
+    fn latest_consensus_rating(
+        &self,
+        for_symbol: Symbol,
+    ) -> impl Future<Output = RequestResult<Option<Bounded<Ratings>>>> + Send;
+}
+
+// ------------------------------------------------------------------------------------------------
+// Trait Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<P> SpawnBlocking<P>
+where
+    P: AnalystRecommendations + Send + Sync + 'static,
+{
+    /// Wrap the synchronous `provider`.
+    pub fn new(provider: P) -> Self {
+        SpawnBlocking {
+            provider: Arc::new(provider),
+        }
+    }
+
+    /// Return a reference to the wrapped provider.
+    pub fn inner(&self) -> &P {
+        &self.provider
+    }
+
+    fn spawn<T, F>(&self, request: F) -> impl Future<Output = RequestResult<T>> + Send
+    where
+        T: Send + 'static,
+        F: FnOnce(&P) -> RequestResult<T> + Send + 'static,
+    {
+        let provider = self.provider.clone();
+        async move {
+            match tokio::task::spawn_blocking(move || request(&provider)).await {
+                Ok(result) => result,
+                Err(err) if err.is_panic() => panic::resume_unwind(err.into_panic()),
+                Err(_) => Err(RequestError::CommunicationError),
+            }
+        }
+    }
+}
+
+impl<P> AsyncRecommendations for SpawnBlocking<P>
+where
+    P: AnalystRecommendations + Send + Sync + 'static,
+{
+    fn target_price(
+        &self,
+        for_symbol: Symbol,
+    ) -> impl Future<Output = RequestResult<Snapshot<PriceTarget>>> + Send {
+        self.spawn(move |p| p.target_price(for_symbol))
+    }
+
+    fn consensus_rating(
+        &self,
+        for_symbol: Symbol,
+    ) -> impl Future<Output = RequestResult<Vec<Bounded<Ratings>>>> + Send {
+        self.spawn(move |p| p.consensus_rating(for_symbol))
+    }
+
+    fn consensus_eps(
+        &self,
+        for_symbol: Symbol,
+    ) -> impl Future<Output = RequestResult<Vec<EPSConsensus>>> + Send {
+        self.spawn(move |p| p.consensus_eps(for_symbol))
+    }
+
+    fn consensus_eps_at(
+        &self,
+        for_symbol: Symbol,
+        as_of: Date,
+    ) -> impl Future<Output = RequestResult<Option<Vec<EPSConsensus>>>> + Send {
+        self.spawn(move |p| p.consensus_eps_at(for_symbol, as_of))
+    }
+
+    fn analyst_estimates(
+        &self,
+        for_symbol: Symbol,
+    ) -> impl Future<Output = RequestResult<Option<Vec<AnalystEstimate>>>> + Send {
+        self.spawn(move |p| p.analyst_estimates(for_symbol))
+    }
+
+    fn rating_change_since(
+        &self,
+        for_symbol: Symbol,
+        since: Date,
+    ) -> impl Future<Output = RequestResult<Option<RatingChangeEvent>>> + Send {
+        self.spawn(move |p| p.rating_change_since(for_symbol, since))
+    }
+
+    fn consensus_rating_many(
+        &self,
+        symbols: &[Symbol],
+    ) -> impl Future<Output = RequestResult<HashMap<Symbol, RequestResult<Vec<Bounded<Ratings>>>>>> + Send
+    {
+        let symbols = symbols.to_vec();
+        self.spawn(move |p| p.consensus_rating_many(&symbols))
+    }
+
+    fn latest_consensus_rating(
+        &self,
+        for_symbol: Symbol,
+    ) -> impl Future<Output = RequestResult<Option<Bounded<Ratings>>>> + Send {
+        self.spawn(move |p| p.latest_consensus_rating(for_symbol))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoCoverage;
+
+    impl AnalystRecommendations for NoCoverage {
+        fn target_price(&self, for_symbol: Symbol) -> RequestResult<Snapshot<PriceTarget>> {
+            Err(RequestError::BadSymbolError(for_symbol))
+        }
+
+        fn consensus_rating(&self, _for_symbol: Symbol) -> RequestResult<Vec<Bounded<Ratings>>> {
+            Ok(Vec::new())
+        }
+
+        fn consensus_eps(&self, _for_symbol: Symbol) -> RequestResult<Vec<EPSConsensus>> {
+            Err(RequestError::Unsupported)
+        }
+    }
+
+    #[test]
+    fn test_spawn_blocking_passes_results_through() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let provider = SpawnBlocking::new(NoCoverage);
+        runtime.block_on(async {
+            match provider.target_price("AAPL".to_string()).await {
+                Err(RequestError::BadSymbolError(symbol)) => assert_eq!(symbol, "AAPL"),
+                _ => panic!("expected a BadSymbolError"),
+            }
+            assert!(provider
+                .consensus_rating("AAPL".to_string())
+                .await
+                .unwrap()
+                .is_empty());
+            match provider.consensus_eps("AAPL".to_string()).await {
+                Err(RequestError::Unsupported) => (),
+                _ => panic!("expected Unsupported"),
+            }
+            let as_of = Date::from_ymd_opt(2019, 6, 1).unwrap();
+            assert!(matches!(
+                provider.consensus_eps_at("AAPL".to_string(), as_of).await,
+                Err(RequestError::Unsupported)
+            ));
+            assert!(matches!(
+                provider.analyst_estimates("AAPL".to_string()).await,
+                Err(RequestError::Unsupported)
+            ));
+            assert_eq!(
+                provider
+                    .rating_change_since("AAPL".to_string(), as_of)
+                    .await
+                    .unwrap(),
+                None
+            );
+            assert!(provider
+                .consensus_rating_many(&["AAPL".to_string(), "MSFT".to_string()])
+                .await
+                .unwrap()
+                .is_empty());
+            assert_eq!(
+                provider
+                    .latest_consensus_rating("AAPL".to_string())
+                    .await
+                    .unwrap(),
+                None
+            );
+        });
+    }
+}
@@ -23,7 +23,13 @@ extern crate chrono;
 #[macro_use]
 extern crate lazy_static;
 extern crate regex;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
 extern crate steel_cent;
+#[cfg(feature = "tokio")]
+extern crate tokio;
 
 // ------------------------------------------------------------------------------------------------
 // Public Modules
@@ -33,14 +39,21 @@ pub mod prelude;
 
 pub mod analysis;
 
+#[cfg(feature = "tokio")]
+pub mod async_analysis;
+
 pub mod classification;
 
 pub mod company;
 
 pub mod market;
 
+pub mod money;
+
 pub mod news;
 
+pub mod portfolio;
+
 pub mod quote;
 
 pub mod registry;
@@ -52,3 +65,6 @@ pub mod provider;
 pub mod request;
 
 pub mod symbol;
+
+#[cfg(feature = "testing")]
+pub mod testing;
@@ -0,0 +1,601 @@
+/*!
+Provides helpers for working with the `Money` type, including currency conversion.
+
+The `Money` type is re-exported from the [steel_cent](https://docs.rs/steel-cent/0.2.3/steel_cent/)
+crate and so additional operations are provided by the `MoneyExt` extension trait rather
+than as inherent methods. Conversion between currencies requires an implementation of the
+`ExchangeRates` _request trait_ that provides the rate to apply on a given date.
+
+Arithmetic that produces fractional minor units, such as `mul_quantity`, rounds
+the result according to a `RoundingMode`; the default is `RoundingMode::HalfUp`
+and each operation has a `*_with` form that takes an explicit mode.
+
+With the `serde` feature the crate serializes `Money` values as an object with
+the ISO currency code and the amount in minor units, for example
+`{"currency": "USD", "minor_amount": 12345}`.
+
+Per-share amounts, such as EPS or a share price, and totals, such as earnings
+or market capitalization, are easily confused when both are plain `Money`.
+Where the basis matters the `PerShare` and `Total` wrappers are used instead, so
+that mixing the two is a compile error; `PerShare::times_shares` is the only
+conversion between them. Each wraps its amount in a public field, so that
+wrapping or unwrapping a plain value is always explicit, as in `PerShare(price)`
+or `eps.consensus.0`. As totals easily exceed the range of `Money`, `Total`
+wraps the wider `steel_cent::Money`, with 64-bit minor units.
+
+Formatting for display is controlled by a `MoneyFormat`, with presets for common
+US and European styles. Note that the `Display` implementation for `Money` is
+that of the steel_cent crate, use `format_with` for control over the output.
+
+## Example
+
+```rust,ignore
+use fin_model::prelude::*;
+
+let converted = price.convert(to_currency, &rates, today)?;
+```
+*/
+
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// The largest number of decimal places `MoneyExt::format_with` will show; a
+/// larger `MoneyFormat::decimal_places` is clamped to this, as every `Money`
+/// amount can then be scaled without overflow.
+pub const MAX_FORMAT_DECIMAL_PLACES: u32 = 9;
+
+/// Options controlling how a `Money` value is formatted by `MoneyExt::format_with`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoneyFormat {
+    /// the (optional) separator between groups of thousands
+    pub thousands_separator: Option<char>,
+    /// the separator between the major and minor parts
+    pub decimal_separator: char,
+    /// if `true` the currency symbol precedes the amount, otherwise it follows
+    /// the amount separated by a space
+    pub symbol_before: bool,
+    /// the (optional) number of decimal places, or the currency's own if `None`;
+    /// at most `MAX_FORMAT_DECIMAL_PLACES` are shown
+    pub decimal_places: Option<u8>,
+}
+
+/// The rounding applied when an arithmetic result falls between two minor
+/// units, such as a half-cent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RoundingMode {
+    /// round to the nearest value, and halves away from zero
+    #[default]
+    HalfUp,
+    /// round to the nearest value, and halves to the even value; also known
+    /// as banker's rounding
+    HalfEven,
+    /// discard any fractional part
+    TowardZero,
+}
+
+/// An amount for a single share, such as EPS, a dividend, or a share price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct PerShare(#[cfg_attr(feature = "serde", serde(with = "serde_money"))] pub Money);
+
+/// An amount for all shares, such as earnings or market capitalization; see
+/// `PerShare::times_shares`. The amount is held in 64-bit minor units, rather
+/// than the 32-bit units of `Money`, so that a market capitalization of
+/// trillions of dollars can be represented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+pub struct Total(pub steel_cent::Money);
+
+// ------------------------------------------------------------------------------------------------
+// Public Traits
+// ------------------------------------------------------------------------------------------------
+
+/// This trait is implemented by providers of currency exchange rates.
+pub trait ExchangeRates {
+    /// Return the multiplier that converts an amount in `from` into an
+    /// amount in `to`, using the rate in effect `on` the given date.
+    fn rate(&self, from: Currency, to: Currency, on: Date) -> RequestResult<f64>;
+}
+
+/// Additional operations on `Money` values.
+pub trait MoneyExt {
+    /// Return the amount as a floating point number of major units.
+    fn as_f64(&self) -> f64;
+
+    /// Convert to the currency `to` using the rate provided by `rates` for the
+    /// date `on`. Converting to the same currency returns the value unchanged
+    /// and does not consult `rates`.
+    fn convert(&self, to: Currency, rates: &impl ExchangeRates, on: Date) -> RequestResult<Money>;
+
+    /// Format the amount, with a currency symbol, according to `opts`.
+    fn format_with(&self, opts: &MoneyFormat) -> String;
+
+    /// Round to the nearest multiple of `tick`, returning `None` if `tick` is
+    /// zero or in a different currency.
+    fn round_to_tick(&self, tick: Money) -> Option<Money> {
+        self.round_to_tick_with(tick, RoundingMode::default())
+    }
+
+    /// Round to a multiple of `tick` using `mode`.
+    fn round_to_tick_with(&self, tick: Money, mode: RoundingMode) -> Option<Money>;
+
+    /// Adjust the amount by `percent`, so that `5.0` is a 5% increase and
+    /// `-5.0` a 5% decrease.
+    fn apply_percent(&self, percent: f64) -> Money {
+        self.apply_percent_with(percent, RoundingMode::default())
+    }
+
+    /// Adjust the amount by `percent`, rounding the result using `mode`.
+    fn apply_percent_with(&self, percent: f64, mode: RoundingMode) -> Money;
+
+    /// Multiply the amount by a, possibly fractional, `quantity`.
+    fn mul_quantity(&self, quantity: f64) -> Money {
+        self.mul_quantity_with(quantity, RoundingMode::default())
+    }
+
+    /// Multiply the amount by `quantity`, rounding the result using `mode`.
+    fn mul_quantity_with(&self, quantity: f64, mode: RoundingMode) -> Money;
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Construct a `Money` value from a floating point number of major units,
+/// rounding to the nearest minor unit of `currency`. Values too large to
+/// represent saturate to the largest positive or negative amount and `NaN` is
+/// zero; use `try_money_from_f64` where such values must be rejected.
+pub fn money_from_f64(currency: Currency, value: f64) -> Money {
+    Money::of_minor(
+        currency,
+        (value * minor_units_per_major(currency)).round() as i32,
+    )
+}
+
+/// Construct a `Money` value as `money_from_f64`, returning `None` if `value`
+/// is not finite or is too large to represent in minor units of `currency`.
+pub fn try_money_from_f64(currency: Currency, value: f64) -> Option<Money> {
+    let minor = (value * minor_units_per_major(currency)).round();
+    if minor.is_finite() && minor >= f64::from(i32::MIN) && minor <= f64::from(i32::MAX) {
+        Some(Money::of_minor(currency, minor as i32))
+    } else {
+        None
+    }
+}
+
+/// Return the signed fractional change from `old` to `new`, `(new - old) / |old|`,
+/// so that `0.5` is a 50% increase. Dividing by the magnitude of `old` means
+/// that a move from `-1.00` to `-0.50` is reported as a `0.5` improvement.
+/// Returns `None` if `old` is zero or the values are in different currencies.
+pub fn pct_change(old: Money, new: Money) -> Option<f64> {
+    if old.currency != new.currency {
+        None
+    } else {
+        let old = old.as_f64();
+        safe_div(new.as_f64() - old, old.abs())
+    }
+}
+
+/// Divide `num` by `den`, returning `None` if the denominator is zero or either
+/// value is not finite. Ratios whose denominator comes from the data, such as a
+/// price or a sum of weights, use this so that an undefined ratio is `None`;
+/// division by a count already known to be non-zero does not need it.
+pub(crate) fn safe_div(num: f64, den: f64) -> Option<f64> {
+    if den == 0.0 || !num.is_finite() || !den.is_finite() {
+        None
+    } else {
+        Some(num / den)
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) mod serde_money {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::prelude::Money;
+
+    #[derive(Serialize, Deserialize)]
+    struct MoneyRepr {
+        currency: String,
+        minor_amount: i32,
+    }
+
+    pub fn serialize<S: Serializer>(money: &Money, serializer: S) -> Result<S::Ok, S::Error> {
+        MoneyRepr {
+            currency: money.currency.code(),
+            minor_amount: money.minor_amount(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        let repr = MoneyRepr::deserialize(deserializer)?;
+        match steel_cent::currency::with_code(&repr.currency) {
+            Some(currency) => Ok(Money::of_minor(currency, repr.minor_amount)),
+            None => Err(serde::de::Error::custom(format!(
+                "unknown currency code {:?}",
+                repr.currency
+            ))),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Trait Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl MoneyFormat {
+    /// US style, for example "$1,234.56".
+    pub fn us() -> Self {
+        MoneyFormat {
+            thousands_separator: Some(','),
+            decimal_separator: '.',
+            symbol_before: true,
+            decimal_places: None,
+        }
+    }
+
+    /// European style, for example "1.234,56 €".
+    pub fn eu() -> Self {
+        MoneyFormat {
+            thousands_separator: Some('.'),
+            decimal_separator: ',',
+            symbol_before: false,
+            decimal_places: None,
+        }
+    }
+}
+
+impl RoundingMode {
+    /// Round `value` to a whole number according to this mode.
+    pub fn round(&self, value: f64) -> f64 {
+        match self {
+            RoundingMode::HalfUp => value.round(),
+            RoundingMode::HalfEven => value.round_ties_even(),
+            RoundingMode::TowardZero => value.trunc(),
+        }
+    }
+}
+
+impl PerShare {
+    /// Return the total for `shares` shares, saturating at the largest amount
+    /// a `Total` can hold.
+    pub fn times_shares(&self, shares: u64) -> Total {
+        let shares = i64::try_from(shares).unwrap_or(i64::MAX);
+        Total(
+            steel_cent::Money::of_minor(self.0.currency, i64::from(self.0.minor_amount()))
+                .saturating_mul(shares),
+        )
+    }
+}
+
+impl Display for PerShare {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Display for Total {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Default for MoneyFormat {
+    fn default() -> Self {
+        MoneyFormat::us()
+    }
+}
+
+impl MoneyExt for Money {
+    fn as_f64(&self) -> f64 {
+        f64::from(self.minor_amount()) / minor_units_per_major(self.currency)
+    }
+
+    fn convert(&self, to: Currency, rates: &impl ExchangeRates, on: Date) -> RequestResult<Money> {
+        if self.currency == to {
+            Ok(*self)
+        } else {
+            let rate = rates.rate(self.currency, to, on)?;
+            Ok(self.convert_to(to, rate))
+        }
+    }
+
+    fn format_with(&self, opts: &MoneyFormat) -> String {
+        let currency_places = u32::from(self.currency.decimal_places());
+        let places = opts
+            .decimal_places
+            .map(u32::from)
+            .unwrap_or(currency_places)
+            .min(MAX_FORMAT_DECIMAL_PLACES);
+        let amount = i64::from(self.minor_amount()).abs();
+        let amount = if places >= currency_places {
+            amount * 10i64.pow(places - currency_places)
+        } else {
+            let divisor = 10i64.pow(currency_places - places);
+            (amount + divisor / 2) / divisor
+        };
+        let scale = 10i64.pow(places);
+
+        let mut number = group_digits(amount / scale, opts.thousands_separator);
+        if places > 0 {
+            number.push(opts.decimal_separator);
+            number.push_str(&format!(
+                "{:0width$}",
+                amount % scale,
+                width = places as usize
+            ));
+        }
+
+        let sign = if self.minor_amount() < 0 { "-" } else { "" };
+        let symbol = currency_symbol(self.currency);
+        if opts.symbol_before {
+            format!("{}{}{}", sign, symbol, number)
+        } else {
+            format!("{}{} {}", sign, number, symbol)
+        }
+    }
+
+    fn round_to_tick_with(&self, tick: Money, mode: RoundingMode) -> Option<Money> {
+        if tick.currency != self.currency || tick.minor_amount() == 0 {
+            None
+        } else {
+            let tick = f64::from(tick.minor_amount());
+            let ticks = mode.round(f64::from(self.minor_amount()) / tick);
+            Some(Money::of_minor(self.currency, (ticks * tick) as i32))
+        }
+    }
+
+    fn apply_percent_with(&self, percent: f64, mode: RoundingMode) -> Money {
+        self.mul_quantity_with(1.0 + percent / 100.0, mode)
+    }
+
+    fn mul_quantity_with(&self, quantity: f64, mode: RoundingMode) -> Money {
+        Money::of_minor(
+            self.currency,
+            mode.round(f64::from(self.minor_amount()) * quantity) as i32,
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn currency_symbol(currency: Currency) -> String {
+    let code = currency.code();
+    match code.as_str() {
+        "USD" => "$".to_string(),
+        "EUR" => "€".to_string(),
+        "GBP" => "£".to_string(),
+        "JPY" => "¥".to_string(),
+        _ => code,
+    }
+}
+
+fn group_digits(value: i64, separator: Option<char>) -> String {
+    let digits = value.to_string();
+    match separator {
+        None => digits,
+        Some(separator) => {
+            let digits: Vec<char> = digits.chars().collect();
+            let groups: Vec<String> = digits
+                .rchunks(3)
+                .rev()
+                .map(|group| group.iter().collect())
+                .collect();
+            groups.join(&separator.to_string())
+        }
+    }
+}
+
+fn minor_units_per_major(currency: Currency) -> f64 {
+    10f64.powi(i32::from(currency.decimal_places()))
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        money_from_f64, pct_change, safe_div, try_money_from_f64, ExchangeRates, MoneyExt,
+        MoneyFormat, PerShare, RoundingMode, Total,
+    };
+    use crate::prelude::*;
+    use steel_cent::currency::{EUR, JPY, USD};
+
+    struct FixedRate(f64);
+
+    impl ExchangeRates for FixedRate {
+        fn rate(&self, _from: Currency, _to: Currency, _on: Date) -> RequestResult<f64> {
+            Ok(self.0)
+        }
+    }
+
+    struct NoRates;
+
+    impl ExchangeRates for NoRates {
+        fn rate(&self, _from: Currency, _to: Currency, _on: Date) -> RequestResult<f64> {
+            Err(RequestError::Unsupported)
+        }
+    }
+
+    #[test]
+    fn test_as_f64() {
+        assert_eq!(Money::of_major_minor(USD, 12, 34).as_f64(), 12.34);
+        assert_eq!(Money::of_major(JPY, 500).as_f64(), 500.0);
+        assert_eq!(money_from_f64(USD, 12.345), Money::of_minor(USD, 1235));
+        assert_eq!(money_from_f64(USD, f64::NAN), Money::of_minor(USD, 0));
+        assert_eq!(money_from_f64(USD, 1e12), Money::of_minor(USD, i32::MAX));
+        assert_eq!(
+            money_from_f64(USD, f64::NEG_INFINITY),
+            Money::of_minor(USD, i32::MIN)
+        );
+    }
+
+    #[test]
+    fn test_try_money_from_f64() {
+        assert_eq!(
+            try_money_from_f64(USD, 12.345),
+            Some(Money::of_minor(USD, 1235))
+        );
+        assert_eq!(
+            try_money_from_f64(JPY, -500.4),
+            Some(Money::of_major(JPY, -500))
+        );
+        assert_eq!(try_money_from_f64(USD, f64::NAN), None);
+        assert_eq!(try_money_from_f64(USD, f64::INFINITY), None);
+        assert_eq!(try_money_from_f64(USD, f64::NEG_INFINITY), None);
+        assert_eq!(try_money_from_f64(USD, 1e12), None);
+        assert_eq!(
+            try_money_from_f64(USD, 21_474_836.47),
+            Some(Money::of_minor(USD, i32::MAX))
+        );
+        assert_eq!(try_money_from_f64(USD, 21_474_836.48), None);
+    }
+
+    #[test]
+    fn test_per_share_times_shares() {
+        let wide = |minor| Total(steel_cent::Money::of_minor(USD, minor));
+        let eps = PerShare(Money::of_minor(USD, 125));
+        assert_eq!(eps.times_shares(0), wide(0));
+        assert_eq!(eps.times_shares(1_000), wide(125_000));
+        assert_eq!(
+            PerShare(Money::of_minor(USD, -125)).times_shares(1_000),
+            wide(-125_000)
+        );
+        assert_eq!(eps.times_shares(100_000_000), wide(12_500_000_000));
+        // a $190.00 share price over 15.5bn shares, a market cap of $2.945tn
+        let price = PerShare(Money::of_major(USD, 190));
+        assert_eq!(
+            price.times_shares(15_500_000_000),
+            Total(steel_cent::Money::of_major(USD, 2_945_000_000_000))
+        );
+        assert_eq!(
+            PerShare(Money::of_minor(USD, i32::MAX)).times_shares(u64::MAX),
+            Total(steel_cent::Money::max(USD))
+        );
+        assert_eq!(eps.to_string(), Money::of_minor(USD, 125).to_string());
+    }
+
+    #[test]
+    fn test_pct_change() {
+        let usd = |cents| Money::of_minor(USD, cents);
+        assert_eq!(pct_change(usd(100), usd(150)), Some(0.5));
+        assert_eq!(pct_change(usd(100), usd(50)), Some(-0.5));
+        assert_eq!(pct_change(usd(0), usd(50)), None);
+        assert_eq!(pct_change(usd(100), Money::of_minor(EUR, 150)), None);
+    }
+
+    #[test]
+    fn test_pct_change_negative_base() {
+        let usd = |cents| Money::of_minor(USD, cents);
+        assert_eq!(pct_change(usd(-100), usd(-50)), Some(0.5));
+        assert_eq!(pct_change(usd(-100), usd(-150)), Some(-0.5));
+        assert_eq!(pct_change(usd(-100), usd(100)), Some(2.0));
+        assert_eq!(pct_change(usd(-100), usd(0)), Some(1.0));
+    }
+
+    #[test]
+    fn test_safe_div() {
+        assert_eq!(safe_div(1.0, 4.0), Some(0.25));
+        assert_eq!(safe_div(-1.0, 4.0), Some(-0.25));
+        assert_eq!(safe_div(1.0, 0.0), None);
+        assert_eq!(safe_div(1.0, -0.0), None);
+        assert_eq!(safe_div(f64::NAN, 1.0), None);
+        assert_eq!(safe_div(1.0, f64::INFINITY), None);
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let usd = |cents| Money::of_minor(USD, cents);
+        let half_cent = |mode| usd(25).mul_quantity_with(0.5, mode);
+        assert_eq!(half_cent(RoundingMode::HalfUp), usd(13));
+        assert_eq!(half_cent(RoundingMode::HalfEven), usd(12));
+        assert_eq!(half_cent(RoundingMode::TowardZero), usd(12));
+        assert_eq!(usd(25).mul_quantity(0.5), usd(13));
+        assert_eq!(
+            usd(-35).mul_quantity_with(0.5, RoundingMode::HalfEven),
+            usd(-18)
+        );
+        assert_eq!(usd(-25).mul_quantity(0.5), usd(-13));
+
+        assert_eq!(usd(250).apply_percent(5.0), usd(263));
+        assert_eq!(
+            usd(250).apply_percent_with(5.0, RoundingMode::HalfEven),
+            usd(262)
+        );
+        assert_eq!(usd(250).apply_percent(-10.0), usd(225));
+
+        assert_eq!(usd(1025).round_to_tick(usd(50)), Some(usd(1050)));
+        assert_eq!(
+            usd(1025).round_to_tick_with(usd(50), RoundingMode::HalfEven),
+            Some(usd(1000))
+        );
+        assert_eq!(
+            usd(1049).round_to_tick_with(usd(50), RoundingMode::TowardZero),
+            Some(usd(1000))
+        );
+        assert_eq!(usd(1025).round_to_tick(usd(0)), None);
+        assert_eq!(usd(1025).round_to_tick(Money::of_minor(EUR, 50)), None);
+    }
+
+    #[test]
+    fn test_convert() {
+        let today = Date::from_ymd_opt(2019, 7, 1).unwrap();
+        let price = Money::of_major_minor(USD, 10, 1);
+        assert_eq!(price.convert(USD, &NoRates, today).unwrap(), price);
+        assert_eq!(
+            price.convert(EUR, &FixedRate(0.5), today).unwrap(),
+            Money::of_minor(EUR, 501)
+        );
+        assert!(price.convert(EUR, &NoRates, today).is_err());
+    }
+
+    #[test]
+    fn test_format_with() {
+        let price = Money::of_major_minor(USD, 1_234_567, 89);
+        assert_eq!(price.format_with(&MoneyFormat::us()), "$1,234,567.89");
+        assert_eq!(
+            Money::of_major_minor(EUR, 1_234, 5).format_with(&MoneyFormat::eu()),
+            "1.234,05 €"
+        );
+        assert_eq!(
+            (-price).format_with(&MoneyFormat {
+                thousands_separator: None,
+                decimal_places: Some(1),
+                ..MoneyFormat::us()
+            }),
+            "-$1234567.9"
+        );
+        assert_eq!(
+            Money::of_major(JPY, 500).format_with(&MoneyFormat::us()),
+            "¥500"
+        );
+        assert_eq!(
+            Money::of_major_minor(USD, 12, 34).format_with(&MoneyFormat {
+                decimal_places: Some(u8::MAX),
+                ..MoneyFormat::us()
+            }),
+            "$12.340000000"
+        );
+        assert_eq!(
+            Money::of_minor(USD, i32::MAX).format_with(&MoneyFormat {
+                thousands_separator: None,
+                decimal_places: Some(9),
+                ..MoneyFormat::us()
+            }),
+            "$21474836.470000000"
+        );
+    }
+}
@@ -0,0 +1,308 @@
+/*!
+Provides types for holdings of securities, and portfolio-level analysis.
+
+A `Position` is simply a quantity of a security, identified by symbol. The
+functions in this module combine positions with the per-symbol values from
+the analysis module, such as `PriceTarget::upside`, weighting each holding
+by its value at the current price.
+*/
+
+use std::collections::HashMap;
+
+use crate::analysis::{PriceTarget, RatingType, Ratings};
+use crate::money::safe_div;
+use crate::prelude::*;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// A holding of some quantity of a security.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    /// the symbol for the security held
+    pub symbol: Symbol,
+    /// the quantity held, which may be fractional, negative values are short positions
+    pub quantity: f64,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Calculate the expected fractional return of a portfolio, given each position
+/// with its price target and current price, as the average of each holding's
+/// `PriceTarget::upside` weighted by the position's value at the current price.
+/// Short positions contribute the negative of their upside, weighted by their
+/// absolute value. Holdings without an upside are ignored, and an empty
+/// portfolio returns `0.0`.
+///
+/// Values are only comparable in a single currency, so holdings priced in a
+/// currency other than that of the first holding are also ignored; use
+/// `portfolio_expected_return_in` for holdings in a number of currencies.
+pub fn portfolio_expected_return(positions: &[(Position, PriceTarget, PerShare)]) -> f64 {
+    let currency = match positions.first() {
+        None => return 0.0,
+        Some((_, _, current)) => current.0.currency,
+    };
+    let (total, gross) = positions
+        .iter()
+        .filter(|(_, _, current)| current.0.currency == currency)
+        .filter_map(|(position, target, current)| {
+            target
+                .upside(*current)
+                .map(|upside| (position.value(*current), upside))
+        })
+        .fold((0.0, 0.0), |(total, gross), (value, upside)| {
+            (total + value * upside, gross + value.abs())
+        });
+    safe_div(total, gross).unwrap_or(0.0)
+}
+
+/// Calculate the expected return of a portfolio, as `portfolio_expected_return`,
+/// for holdings in a number of currencies. Each target and current price is
+/// converted to `base` using the rates `on` the given date before weighting,
+/// values already in `base` are used unchanged.
+pub fn portfolio_expected_return_in(
+    positions: &[(Position, PriceTarget, PerShare)],
+    base: Currency,
+    rates: &impl ExchangeRates,
+    on: Date,
+) -> RequestResult<f64> {
+    let converted = positions
+        .iter()
+        .map(|(position, target, current)| {
+            Ok((
+                position.clone(),
+                target.convert(base, rates, on)?,
+                PerShare(current.0.convert(base, rates, on)?),
+            ))
+        })
+        .collect::<RequestResult<Vec<_>>>()?;
+    Ok(portfolio_expected_return(&converted))
+}
+
+/// Decompose the expected return of a portfolio by the consensus rating of its
+/// holdings, see `Ratings::consensus_type`. Each holding contributes its
+/// `PriceTarget::upside` weighted by its share of the gross value of the
+/// portfolio, as `portfolio_expected_return`, so the contributions of all
+/// ratings sum to the expected return of the rated holdings. Holdings with no
+/// ratings, or without an upside, are skipped.
+pub fn upside_by_rating(
+    holdings: &[(Position, Ratings, PriceTarget, PerShare)],
+) -> HashMap<RatingType, f64> {
+    let rated: Vec<(RatingType, f64, f64)> = holdings
+        .iter()
+        .filter_map(|(position, ratings, target, current)| {
+            Some((
+                ratings.consensus_type()?,
+                position.value(*current),
+                target.upside(*current)?,
+            ))
+        })
+        .collect();
+    let gross: f64 = rated.iter().map(|(_, value, _)| value.abs()).sum();
+    let mut contributions = HashMap::new();
+    for (rating, value, upside) in rated {
+        if let Some(weight) = safe_div(value, gross) {
+            *contributions.entry(rating).or_insert(0.0) += weight * upside;
+        }
+    }
+    contributions
+}
+
+/// Measure the tilt of a portfolio towards, or away from, the names favored by
+/// analysts, given each position with its ratings and current price. Each
+/// holding contributes the difference between its `Ratings::scaled_average`
+/// and the equal-weighted average of all holdings, weighted by its share of the
+/// gross value of the portfolio, as `portfolio_expected_return`. As lower
+/// averages are more bullish, a negative tilt means the portfolio is overweight
+/// the more bullishly rated names; a short position in a name counts against
+/// it, so shorting bearishly rated names also tilts the portfolio bullish. A
+/// portfolio of long positions only has a tilt of the value-weighted average
+/// less the equal-weighted average. Holdings with no ratings are excluded from
+/// both averages, and `None` is returned if there are no rated holdings or
+/// their gross value is zero.
+pub fn consensus_tilt(holdings: &[(Position, Ratings, PerShare)]) -> Option<f64> {
+    let rated: Vec<(f64, f64)> = holdings
+        .iter()
+        .filter(|(_, ratings, _)| !ratings.is_empty())
+        .map(|(position, ratings, current)| (position.value(*current), ratings.scaled_average()))
+        .collect();
+    let gross: f64 = rated.iter().map(|(value, _)| value.abs()).sum();
+    let equal = safe_div(
+        rated.iter().map(|(_, average)| average).sum(),
+        rated.len() as f64,
+    )?;
+    safe_div(
+        rated
+            .iter()
+            .map(|(value, average)| value * (average - equal))
+            .sum(),
+        gross,
+    )
+}
+
+// ------------------------------------------------------------------------------------------------
+// Trait Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Position {
+    /// Construct a new position.
+    pub fn new(symbol: Symbol, quantity: f64) -> Self {
+        Position { symbol, quantity }
+    }
+
+    /// Return the value of the position at `price`, in major units of the
+    /// price's currency.
+    pub fn value(&self, price: PerShare) -> f64 {
+        self.quantity * price.0.as_f64()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use steel_cent::currency::{GBP, USD};
+
+    struct FixedRate(f64);
+
+    impl ExchangeRates for FixedRate {
+        fn rate(&self, _from: Currency, _to: Currency, _on: Date) -> RequestResult<f64> {
+            Ok(self.0)
+        }
+    }
+
+    fn holding(quantity: f64, average: i32, current: i32) -> (Position, PriceTarget, PerShare) {
+        let usd = |major| PerShare(Money::of_major(USD, major));
+        (
+            Position::new("AAPL".to_string(), quantity),
+            PriceTarget {
+                high: usd(average * 2),
+                low: usd(0),
+                average: usd(average),
+                number_of_analysts: 10,
+            },
+            usd(current),
+        )
+    }
+
+    #[test]
+    fn test_portfolio_expected_return() {
+        assert_eq!(portfolio_expected_return(&[]), 0.0);
+        assert_eq!(portfolio_expected_return(&[holding(10.0, 110, 100)]), 0.1);
+
+        // 1,000 at 10% upside and 3,000 at 20% downside.
+        let positions = [holding(10.0, 110, 100), holding(60.0, 40, 50)];
+        assert!((portfolio_expected_return(&positions) - (-0.125)).abs() < 1e-12);
+
+        // a short position gains from the downside.
+        let positions = [holding(-60.0, 40, 50)];
+        assert!((portfolio_expected_return(&positions) - 0.2).abs() < 1e-12);
+
+        assert_eq!(portfolio_expected_return(&[holding(10.0, 110, 0)]), 0.0);
+    }
+
+    #[test]
+    fn test_portfolio_expected_return_mixed_currencies() {
+        let gbp = |major| PerShare(Money::of_major(GBP, major));
+        let london = (
+            Position::new("VOD".to_string(), 1000.0),
+            PriceTarget {
+                high: gbp(200),
+                low: gbp(0),
+                average: gbp(50),
+                number_of_analysts: 10,
+            },
+            gbp(100),
+        );
+        let positions = [holding(10.0, 110, 100), london.clone()];
+        assert!((portfolio_expected_return(&positions) - 0.1).abs() < 1e-12);
+        let positions = [london, holding(10.0, 110, 100)];
+        assert!((portfolio_expected_return(&positions) - (-0.5)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_portfolio_expected_return_in() {
+        let on = Date::from_ymd_opt(2019, 6, 1).unwrap();
+        let gbp = |major| PerShare(Money::of_major(GBP, major));
+        let london = (
+            Position::new("VOD".to_string(), 10.0),
+            PriceTarget {
+                high: gbp(200),
+                low: gbp(0),
+                average: gbp(90),
+                number_of_analysts: 10,
+            },
+            gbp(100),
+        );
+        // 1,000 USD at 10% upside, and £1,000 (2,000 USD) at 10% downside.
+        let positions = [holding(10.0, 110, 100), london];
+        let expected = portfolio_expected_return_in(&positions, USD, &FixedRate(2.0), on).unwrap();
+        assert!((expected - (-0.1 / 3.0)).abs() < 1e-12);
+
+        let domestic = [holding(10.0, 110, 100)];
+        assert_eq!(
+            portfolio_expected_return_in(&domestic, USD, &FixedRate(f64::NAN), on).unwrap(),
+            0.1
+        );
+    }
+
+    #[test]
+    fn test_upside_by_rating() {
+        let rated =
+            |ratings: Ratings, (position, target, current)| (position, ratings, target, current);
+        let of = |rating: Option<RatingType>| -> Ratings { rating.into_iter().collect() };
+        // 1,000 and 1,000 at 10% upside rated buy, 2,000 at 20% downside rated sell.
+        let holdings = [
+            rated(of(Some(RatingType::Buy)), holding(10.0, 110, 100)),
+            rated(of(Some(RatingType::Buy)), holding(10.0, 110, 100)),
+            rated(of(Some(RatingType::Sell)), holding(40.0, 40, 50)),
+            rated(of(None), holding(100.0, 200, 100)),
+        ];
+        let contributions = upside_by_rating(&holdings);
+        assert_eq!(contributions.len(), 2);
+        assert!((contributions[&RatingType::Buy] - 0.05).abs() < 1e-12);
+        assert!((contributions[&RatingType::Sell] - (-0.1)).abs() < 1e-12);
+        assert!(upside_by_rating(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_consensus_tilt() {
+        let usd = |major| PerShare(Money::of_major(USD, major));
+        let rated = |quantity, rating: Option<RatingType>| {
+            (
+                Position::new("AAPL".to_string(), quantity),
+                rating.into_iter().collect::<Ratings>(),
+                usd(100),
+            )
+        };
+        // equal weights average 3.0, 3,000 in buy and 1,000 in sell averages 2.0
+        let holdings = [
+            rated(30.0, Some(RatingType::Buy)),
+            rated(10.0, Some(RatingType::Sell)),
+            rated(100.0, None),
+        ];
+        assert_eq!(consensus_tilt(&holdings), Some(-1.0));
+        assert_eq!(consensus_tilt(&holdings[..1]), Some(0.0));
+        assert_eq!(consensus_tilt(&holdings[2..]), None);
+        assert_eq!(consensus_tilt(&[]), None);
+
+        // shorting the sell-rated name adds to the bullish tilt, rather than
+        // offsetting it as a long position does.
+        let holdings = [
+            rated(30.0, Some(RatingType::Buy)),
+            rated(-10.0, Some(RatingType::Sell)),
+        ];
+        assert_eq!(consensus_tilt(&holdings), Some(-2.0));
+        let holdings = [
+            rated(-30.0, Some(RatingType::Buy)),
+            rated(10.0, Some(RatingType::Sell)),
+        ];
+        assert_eq!(consensus_tilt(&holdings), Some(2.0));
+    }
+}
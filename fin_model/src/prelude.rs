@@ -2,17 +2,28 @@
 Re-exports base types required to use _request traits_.
 
 This can be used before any of the request trait modules, it not only
-exports all of the locally defined types but also a `Money` type from
-the [steel_cent](https://docs.rs/steel-cent/0.2.3/steel_cent/) crate and
+exports all of the locally defined types but also `Money` and `Currency` types
+from the [steel_cent](https://docs.rs/steel-cent/0.2.3/steel_cent/) crate and
 `Date` and `DateTime` types from the [chrono](https://docs.rs/crate/chrono/0.4.7)
 crate.
 */
 
+use std::fmt::{self, Display, Formatter};
 use std::time::Duration;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use steel_cent::currency::Currency;
 pub use steel_cent::SmallMoney as Money;
 
+/// A calendar date only, with no time of day or timezone. Dates such as
+/// fiscal period ends and report dates are the local date of the issuer;
+/// use `date_from_ymd` for a checked constructor and `today_utc` for the
+/// current date.
 pub use chrono::NaiveDate as Date;
+/// A date and time of day, with no timezone; values from providers are
+/// in UTC unless documented otherwise.
 pub use chrono::NaiveDateTime as DateTime;
 
 // ------------------------------------------------------------------------------------------------
@@ -20,22 +31,46 @@ pub use chrono::NaiveDateTime as DateTime;
 // ------------------------------------------------------------------------------------------------
 
 /// A snapshot value; `data` with a `date`, usually the last updated
-/// or or calculated date and time.
-#[derive(Debug)]
+/// or or calculated date and time. Construct with `new`, and
+/// `with_attribution` or `with_source` where the provider is known; these are
+/// read with `attribution` and `source`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Snapshot<T> {
     pub date: DateTime,
+    /// the (optional) attribution of the provider that produced the value
+    attribution: Option<Attribution>,
     pub data: T,
 }
 
 /// A time-bounded value; `data` with a `start_date` and `end_date`
 /// signifying the range within which the data is considered valid.
-#[derive(Debug)]
+/// Construct with `new`, and `with_attribution` or `with_source` where the
+/// provider is known; these are read with `attribution` and `source`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Bounded<T> {
     pub start_date: DateTime,
     pub end_date: DateTime,
+    /// the (optional) attribution of the provider that produced the value
+    attribution: Option<Attribution>,
     pub data: T,
 }
 
+/// The source of a value, for display alongside it; such as "Source: IEX
+/// Cloud as of 2024-03-01". See `Snapshot::attribution` and
+/// `Provider::attribution_today`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Attribution {
+    /// the name of the provider, see `Provider::attribution`
+    pub provider: String,
+    /// the date on which the value was retrieved from the provider
+    pub as_of: Date,
+    /// any licensing or usage terms that must be displayed with the value
+    pub license_note: Option<String>,
+}
+
 /// Represents a `series` of data points, over the time period indicated
 /// by `interval`, with values separated by `intra_interval`.
 #[derive(Debug)]
@@ -48,10 +83,142 @@ pub struct Series<I, T> {
     pub series: Vec<T>,
 }
 
+/// Errors that can result from constructing a `Date`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateError {
+    /// the year, month, and day do not form a valid calendar date
+    InvalidDate { year: i32, month: u32, day: u32 },
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Construct a `Date`, returning an error rather than panicking if the values
+/// are out of range or do not form a valid date, such as February 30th.
+pub fn date_from_ymd(year: i32, month: u32, day: u32) -> Result<Date, DateError> {
+    Date::from_ymd_opt(year, month, day).ok_or(DateError::InvalidDate { year, month, day })
+}
+
+/// Return the current date in UTC.
+pub fn today_utc() -> Date {
+    chrono::Utc::now().naive_utc().date()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Trait Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<T> Snapshot<T> {
+    /// Construct a new snapshot, with no `attribution`.
+    pub fn new(date: DateTime, data: T) -> Self {
+        Snapshot {
+            date,
+            attribution: None,
+            data,
+        }
+    }
+
+    /// Set the name of the provider that produced this value; the attribution
+    /// is as of the snapshot `date`, use `with_attribution` to set another.
+    pub fn with_source(self, source: impl Into<String>) -> Self {
+        let as_of = self.date.date();
+        self.with_attribution(Attribution::new(source, as_of))
+    }
+
+    /// Set the attribution of the provider that produced this value.
+    pub fn with_attribution(self, attribution: Attribution) -> Self {
+        Snapshot {
+            attribution: Some(attribution),
+            ..self
+        }
+    }
+
+    /// Return the attribution of the provider that produced this value, if
+    /// known.
+    pub fn attribution(&self) -> Option<&Attribution> {
+        self.attribution.as_ref()
+    }
+
+    /// Return the name of the provider that produced this value, if known.
+    pub fn source(&self) -> Option<&str> {
+        self.attribution().map(|a| a.provider.as_str())
+    }
+}
+
+impl<T> Bounded<T> {
+    /// Construct a new bounded value, with no `attribution`.
+    pub fn new(start_date: DateTime, end_date: DateTime, data: T) -> Self {
+        Bounded {
+            start_date,
+            end_date,
+            attribution: None,
+            data,
+        }
+    }
+
+    /// Set the name of the provider that produced this value; the attribution
+    /// is as of the `end_date`, use `with_attribution` to set another.
+    pub fn with_source(self, source: impl Into<String>) -> Self {
+        let as_of = self.end_date.date();
+        self.with_attribution(Attribution::new(source, as_of))
+    }
+
+    /// Set the attribution of the provider that produced this value.
+    pub fn with_attribution(self, attribution: Attribution) -> Self {
+        Bounded {
+            attribution: Some(attribution),
+            ..self
+        }
+    }
+
+    /// Return the attribution of the provider that produced this value, if
+    /// known.
+    pub fn attribution(&self) -> Option<&Attribution> {
+        self.attribution.as_ref()
+    }
+
+    /// Return the name of the provider that produced this value, if known.
+    pub fn source(&self) -> Option<&str> {
+        self.attribution().map(|a| a.provider.as_str())
+    }
+}
+
+impl Attribution {
+    /// Construct a new attribution, with no `license_note`.
+    pub fn new(provider: impl Into<String>, as_of: Date) -> Self {
+        Attribution {
+            provider: provider.into(),
+            as_of,
+            license_note: None,
+        }
+    }
+
+    /// Set the licensing or usage terms to display with the value.
+    pub fn with_license_note(self, license_note: impl Into<String>) -> Self {
+        Attribution {
+            license_note: Some(license_note.into()),
+            ..self
+        }
+    }
+}
+
+impl Display for Attribution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Source: {} as of {}", self.provider, self.as_of)?;
+        if let Some(note) = &self.license_note {
+            write!(f, " ({})", note)?;
+        }
+        Ok(())
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // Re-Exported Types
 // ------------------------------------------------------------------------------------------------
 
+pub use crate::money::{pct_change, ExchangeRates, MoneyExt, PerShare, RoundingMode, Total};
+
 pub use crate::provider::Provider;
 
 pub use crate::registry::Registry;
@@ -59,3 +226,79 @@ pub use crate::registry::Registry;
 pub use crate::request::{RequestError, RequestResult};
 
 pub use crate::symbol::{Symbol, Symbols};
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_from_ymd() {
+        assert_eq!(
+            date_from_ymd(2024, 2, 29),
+            Ok(Date::from_ymd_opt(2024, 2, 29).unwrap())
+        );
+        assert_eq!(
+            date_from_ymd(2024, 2, 30),
+            Err(DateError::InvalidDate {
+                year: 2024,
+                month: 2,
+                day: 30
+            })
+        );
+        assert!(date_from_ymd(2023, 2, 29).is_err());
+        assert!(date_from_ymd(2024, 13, 1).is_err());
+        assert!(date_from_ymd(2024, 0, 1).is_err());
+    }
+
+    #[test]
+    fn test_attribution_display() {
+        let as_of = Date::from_ymd_opt(2024, 3, 1).unwrap();
+        let attribution = Attribution::new("IEX Cloud", as_of);
+        assert_eq!(
+            attribution.to_string(),
+            "Source: IEX Cloud as of 2024-03-01"
+        );
+        assert_eq!(
+            attribution
+                .with_license_note("Data provided by IEX Cloud")
+                .to_string(),
+            "Source: IEX Cloud as of 2024-03-01 (Data provided by IEX Cloud)"
+        );
+    }
+
+    #[test]
+    fn test_snapshot_attribution() {
+        let date = Date::from_ymd_opt(2024, 3, 1).unwrap();
+        let snapshot = Snapshot::new(date.and_hms_opt(16, 0, 0).unwrap(), 2);
+        assert_eq!(snapshot.attribution(), None);
+        assert_eq!(snapshot.source(), None);
+
+        let sourced = snapshot.clone().with_source("IEX Cloud");
+        assert_eq!(sourced.source(), Some("IEX Cloud"));
+        assert_eq!(
+            sourced.attribution(),
+            Some(&Attribution::new("IEX Cloud", date))
+        );
+
+        let later = date.succ_opt().unwrap();
+        let attribution = Attribution::new("IEX Cloud", later).with_license_note("terms");
+        let attributed = snapshot.with_attribution(attribution.clone());
+        assert_eq!(attributed.source(), Some("IEX Cloud"));
+        assert_eq!(attributed.attribution(), Some(&attribution));
+
+        let bounded = Bounded::new(
+            date.and_hms_opt(0, 0, 0).unwrap(),
+            later.and_hms_opt(0, 0, 0).unwrap(),
+            2,
+        )
+        .with_source("IEX Cloud");
+        assert_eq!(
+            bounded.attribution(),
+            Some(&Attribution::new("IEX Cloud", later))
+        );
+    }
+}
@@ -7,8 +7,31 @@ implement the `Provider` trait. This is the mechanism by which a client
 instantiates a specific service provider.
 */
 
+use std::time::Duration;
+
+use crate::prelude::{today_utc, Attribution};
 use crate::request::RequestResult;
 
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+/// The caching, retry, and rate-limiting behavior of a wrapped provider, such as
+/// `analysis::Wrapped`. Each behavior is disabled when its value is `None`, or
+/// zero, and so the default policy disables all of them.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProviderPolicy {
+    /// how long a successful result is cached for
+    pub ttl: Option<Duration>,
+    /// the maximum number of attempts for requests that fail with a transient
+    /// error; `0` and `1` both make a single attempt
+    pub max_attempts: u32,
+    /// the delay before the first retry, doubling for each later retry
+    pub backoff_base: Duration,
+    /// the minimum interval between requests made of the provider
+    pub min_interval: Option<Duration>,
+}
+
 // ------------------------------------------------------------------------------------------------
 // Public Traits
 // ------------------------------------------------------------------------------------------------
@@ -34,4 +57,20 @@ where
 
     /// Optional, if the provider needs to close or complete any resources when done.
     fn finish(&self);
+
+    /// Any licensing or usage terms that must be displayed with values from
+    /// the provider; by default there are none.
+    fn license_note(&self) -> Option<String> {
+        None
+    }
+
+    /// The attribution for values retrieved from this provider today, to set
+    /// with `Snapshot::with_attribution` or `Bounded::with_attribution`.
+    fn attribution_today(&self) -> Attribution {
+        Attribution {
+            provider: self.attribution(),
+            as_of: today_utc(),
+            license_note: self.license_note(),
+        }
+    }
 }
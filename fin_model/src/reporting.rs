@@ -10,14 +10,22 @@ also contains a date for the fiscal year start.
 
 The financial period type implements both `fmt::Display` and `str::FromStr`
 and so supports the ability to read and write the period as a string in a
-common manner.
+common manner. In addition to the displayed form, "H1 2019", the parser
+accepts the ISO-like form "2019-H1", which is displayed with the alternate
+flag, `format!("{:#}", period)`. With the `serde` feature the period is
+serialized as its displayed string.
 */
 
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt;
 use std::fmt::Display;
 use std::str::FromStr;
 
+use chrono::Datelike;
 use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::prelude::*;
 
@@ -31,7 +39,14 @@ use crate::prelude::*;
 /// By default the assumption is that periods represent calendar time;
 /// for example, the values "2019", "Q1 2019", and "H1 2019" are all assumed
 /// to start on January 1st.
-#[derive(Debug, PartialEq)]
+///
+/// Periods are ordered by the month in which they end, and then by length, so
+/// that within a year "Q1" < "Q2" < "H1" < "Q3" < "Q4" < "H2" < the year
+/// itself. Mixed periods therefore sort after all of the shorter periods that
+/// they contain. Invalid periods, such as "Q0 2020", may end in the same month
+/// as a valid one, and so are then ordered by year and then by quarter or half;
+/// the order is consistent with equality but otherwise not meaningful.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FinancialPeriod {
     Quarter {
         /// the quarter within the year (values: 1..4)
@@ -55,7 +70,7 @@ pub enum FinancialPeriod {
 /// to represent years that do not align with the calendar start date.
 ///
 /// For example, "Q1 2019" with a start date of April 1st ends on June 30th.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FiscalPeriod {
     /// the period within the fiscal year
     pub period: FinancialPeriod,
@@ -63,10 +78,49 @@ pub struct FiscalPeriod {
     pub fiscal_year_start_date: Date,
 }
 
+/// Identifies the fiscal calendar that a `FinancialPeriod` is relative to, by
+/// the month in which the fiscal year starts. The default is the calendar
+/// year, starting in January.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FiscalCalendar {
+    /// the month the fiscal year starts (values: 1..12)
+    pub start_month: u32,
+}
+
 // ------------------------------------------------------------------------------------------------
 // Trait Implementations
 // ------------------------------------------------------------------------------------------------
 
+impl Default for FiscalCalendar {
+    fn default() -> Self {
+        FiscalCalendar { start_month: 1 }
+    }
+}
+
+impl FiscalCalendar {
+    /// Derive the calendar in which `period` ends on `end_date`, for providers
+    /// that report a fiscal period with its end date but not the calendar; the
+    /// start month is taken from the month of `end_date`. Returns `None` if the
+    /// period is not valid, or `end_date` does not fall within the period in
+    /// the derived calendar, such as the end of "Q1 2020" in 2021.
+    pub fn from_period_end(period: &FinancialPeriod, end_date: Date) -> Option<FiscalCalendar> {
+        if !period.is_valid() {
+            return None;
+        }
+        let (year, _) = period.year_and_number();
+        let end_month = period.end_month_index() - i32::from(year) * 12;
+        let calendar = FiscalCalendar {
+            start_month: (end_date.month() as i32 - end_month).rem_euclid(12) as u32 + 1,
+        };
+        if period.contains_in(end_date, &calendar) {
+            Some(calendar)
+        } else {
+            None
+        }
+    }
+}
+
 impl FinancialPeriod {
     /// Validate the period to ensure that quarter, half, and year
     /// values are within correct ranges.
@@ -81,18 +135,205 @@ impl FinancialPeriod {
             FinancialPeriod::Year { year } => is_valid_year(*year),
         }
     }
+
+    /// Return the first day of the period, relative to January 1st.
+    ///
+    /// # Panics
+    ///
+    /// If the period is not valid.
+    pub fn start_date(&self) -> Date {
+        self.start_date_in(&FiscalCalendar::default())
+    }
+
+    /// Return the last day of the period, relative to January 1st.
+    ///
+    /// # Panics
+    ///
+    /// If the period is not valid.
+    pub fn end_date(&self) -> Date {
+        self.end_date_in(&FiscalCalendar::default())
+    }
+
+    /// Returns `true` if `date` falls within the period, inclusive of both
+    /// the start and end dates. An invalid period contains no dates.
+    pub fn contains(&self, date: Date) -> bool {
+        self.contains_in(date, &FiscalCalendar::default())
+    }
+
+    /// Return the first day of the period within the fiscal `calendar`. Fiscal
+    /// years are named for the calendar year in which they end, so with a
+    /// fiscal year starting in October "Q1 2020" starts on October 1st 2019.
+    ///
+    /// # Panics
+    ///
+    /// If the period, or the calendar, is not valid.
+    pub fn start_date_in(&self, calendar: &FiscalCalendar) -> Date {
+        first_day_of_month(self.valid_start_month_index(), calendar)
+    }
+
+    /// Return the last day of the period within the fiscal `calendar`, see
+    /// `start_date_in`.
+    ///
+    /// # Panics
+    ///
+    /// If the period, or the calendar, is not valid.
+    pub fn end_date_in(&self, calendar: &FiscalCalendar) -> Date {
+        let months = self.valid_start_month_index() + self.months();
+        first_day_of_month(months, calendar)
+            .pred_opt()
+            .expect("invalid financial period")
+    }
+
+    /// Returns `true` if `date` falls within the period in the fiscal `calendar`.
+    /// An invalid period contains no dates.
+    ///
+    /// # Panics
+    ///
+    /// If the calendar is not valid.
+    pub fn contains_in(&self, date: Date, calendar: &FiscalCalendar) -> bool {
+        self.is_valid()
+            && date >= self.start_date_in(calendar)
+            && date <= self.end_date_in(calendar)
+    }
+
+    /// Return the period of the same length that immediately follows this one;
+    /// for example, "Q4 2019" is followed by "Q1 2020" and "H1 2019" by "H2 2019".
+    /// Returns `None` if this period is not valid, or the following period
+    /// would not be, as it is after 9999.
+    pub fn next(&self) -> Option<FinancialPeriod> {
+        self.offset(1)
+    }
+
+    /// Return the period of the same length that immediately precedes this one;
+    /// for example, "Q1 2020" is preceded by "Q4 2019" and "H1 2020" by "H2 2019".
+    /// Returns `None` if this period is not valid, or the preceding period
+    /// would not be, as it is before 1900.
+    pub fn previous(&self) -> Option<FinancialPeriod> {
+        self.offset(-1)
+    }
+
+    /// Return the number of periods of this length in a year; `4` for quarters,
+    /// `2` for halves, and `1` for years.
+    pub fn periods_per_year(&self) -> u32 {
+        match self {
+            FinancialPeriod::Quarter { .. } => 4,
+            FinancialPeriod::Half { .. } => 2,
+            FinancialPeriod::Year { .. } => 1,
+        }
+    }
+
+    /// Return the number of periods of this length from the end of this period
+    /// to the end of `other`, negative if `other` ends first; so "Q1 2019" to
+    /// "Q3 2020" is `6`. Where `other` is a different length the distance is
+    /// truncated toward zero, so "Q4 2019" to "2020" is `4`, and "2019" to
+    /// "Q1 2020" is `0`.
+    pub fn distance(&self, other: &FinancialPeriod) -> i32 {
+        let months = other.end_month_index() - self.end_month_index();
+        months / (12 / self.periods_per_year() as i32)
+    }
+
+    fn end_month_index(&self) -> i32 {
+        let (year, end_month) = match self {
+            FinancialPeriod::Quarter { quarter, year } => (year, i32::from(*quarter) * 3),
+            FinancialPeriod::Half { half, year } => (year, i32::from(*half) * 6),
+            FinancialPeriod::Year { year } => (year, 12),
+        };
+        i32::from(*year) * 12 + end_month
+    }
+
+    fn months(&self) -> i32 {
+        12 / self.periods_per_year() as i32
+    }
+
+    fn valid_start_month_index(&self) -> i32 {
+        if !self.is_valid() {
+            panic!("invalid financial period {:?}", self);
+        }
+        self.end_month_index() - self.months()
+    }
+
+    fn year_and_number(&self) -> (u16, u8) {
+        match self {
+            FinancialPeriod::Quarter { quarter, year } => (*year, *quarter),
+            FinancialPeriod::Half { half, year } => (*year, *half),
+            FinancialPeriod::Year { year } => (*year, 1),
+        }
+    }
+
+    fn offset(&self, periods: i32) -> Option<FinancialPeriod> {
+        if !self.is_valid() {
+            return None;
+        }
+        let per_year = self.periods_per_year() as i32;
+        let (year, number) = self.year_and_number();
+        let index = i32::from(year) * per_year + i32::from(number) - 1 + periods;
+        let year = u16::try_from(index.div_euclid(per_year)).ok()?;
+        let number = (index.rem_euclid(per_year) + 1) as u8;
+        let period = match self {
+            FinancialPeriod::Quarter { .. } => FinancialPeriod::Quarter {
+                quarter: number,
+                year,
+            },
+            FinancialPeriod::Half { .. } => FinancialPeriod::Half { half: number, year },
+            FinancialPeriod::Year { .. } => FinancialPeriod::Year { year },
+        };
+        if period.is_valid() {
+            Some(period)
+        } else {
+            None
+        }
+    }
 }
 
+/// Displays the period as "Q2 2019", "H1 2019", or "2019"; or with the
+/// alternate flag, `{:#}`, in the ISO-like form "2019-Q2", "2019-H1", or "2019".
 impl Display for FinancialPeriod {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            FinancialPeriod::Quarter { quarter, year } => write!(f, "Q{} {}", quarter, year),
-            FinancialPeriod::Half { half, year } => write!(f, "H{} {}", half, year),
-            FinancialPeriod::Year { year } => write!(f, "{}", year),
+        match (self, f.alternate()) {
+            (FinancialPeriod::Quarter { quarter, year }, false) => {
+                write!(f, "Q{} {}", quarter, year)
+            }
+            (FinancialPeriod::Quarter { quarter, year }, true) => {
+                write!(f, "{}-Q{}", year, quarter)
+            }
+            (FinancialPeriod::Half { half, year }, false) => write!(f, "H{} {}", half, year),
+            (FinancialPeriod::Half { half, year }, true) => write!(f, "{}-H{}", year, half),
+            (FinancialPeriod::Year { year }, _) => write!(f, "{}", year),
         }
     }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for FinancialPeriod {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for FinancialPeriod {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        FinancialPeriod::from_str(&s)
+            .map_err(|e| serde::de::Error::custom(format!("invalid period {:?}: {:?}", s, e)))
+    }
+}
+
+impl PartialOrd for FinancialPeriod {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FinancialPeriod {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.end_month_index()
+            .cmp(&other.end_month_index())
+            .then_with(|| other.periods_per_year().cmp(&self.periods_per_year()))
+            .then_with(|| self.year_and_number().cmp(&other.year_and_number()))
+    }
+}
+
 /// Errors that can result from parsing a `FinancialPeriod` from a string.
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
@@ -109,7 +350,8 @@ impl FromStr for FinancialPeriod {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         lazy_static! {
-            static ref RE: Regex = Regex::new(r"^(([QH])(\d) )?(\d{4})$").unwrap();
+            static ref RE: Regex =
+                Regex::new(r"^(?:(?:([QH])(\d) )?(\d{4})|(\d{4})-([QH])(\d))$").unwrap();
         }
 
         if s.is_empty() {
@@ -119,22 +361,21 @@ impl FromStr for FinancialPeriod {
         let period = match RE.captures(s) {
             None => Err(ParseError::InvalidPeriodString),
             Some(captures) => {
-                if let Some(period) = captures.get(2) {
-                    if period.as_str() == "Q" {
-                        Ok(FinancialPeriod::Quarter {
-                            quarter: u8::from_str(&captures[3]).unwrap(),
-                            year: u16::from_str(&captures[4]).unwrap(),
-                        })
-                    } else {
-                        Ok(FinancialPeriod::Half {
-                            half: u8::from_str(&captures[3]).unwrap(),
-                            year: u16::from_str(&captures[4]).unwrap(),
-                        })
-                    }
-                } else {
-                    Ok(FinancialPeriod::Year {
-                        year: u16::from_str(&captures[4]).unwrap(),
-                    })
+                let (kind, number, year) = match captures.get(3) {
+                    Some(year) => (captures.get(1), captures.get(2), year),
+                    None => (captures.get(5), captures.get(6), captures.get(4).unwrap()),
+                };
+                let year = u16::from_str(year.as_str()).unwrap();
+                match (kind.map(|k| k.as_str()), number) {
+                    (Some("Q"), Some(number)) => Ok(FinancialPeriod::Quarter {
+                        quarter: u8::from_str(number.as_str()).unwrap(),
+                        year,
+                    }),
+                    (Some(_), Some(number)) => Ok(FinancialPeriod::Half {
+                        half: u8::from_str(number.as_str()).unwrap(),
+                        year,
+                    }),
+                    _ => Ok(FinancialPeriod::Year { year }),
                 }
             }
         };
@@ -159,13 +400,25 @@ fn is_valid_year(year: u16) -> bool {
     year >= 1900 && year <= 9999
 }
 
+/// The first day of the month `months` after January of year zero, shifted to
+/// the fiscal `calendar`.
+fn first_day_of_month(months: i32, calendar: &FiscalCalendar) -> Date {
+    let shift = i32::try_from(calendar.start_month).expect("invalid fiscal calendar")
+        - 1
+        - if calendar.start_month > 1 { 12 } else { 0 };
+    let months = months + shift;
+    Date::from_ymd_opt(months.div_euclid(12), months.rem_euclid(12) as u32 + 1, 1)
+        .expect("invalid financial period")
+}
+
 // ------------------------------------------------------------------------------------------------
 // Unit Tests
 // ------------------------------------------------------------------------------------------------
 
 #[cfg(test)]
 mod tests {
-    use super::{is_valid_year, FinancialPeriod, ParseError};
+    use super::{is_valid_year, FinancialPeriod, FiscalCalendar, ParseError};
+    use crate::prelude::Date;
     use std::str::FromStr;
 
     #[test]
@@ -243,6 +496,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_to_iso_like_string() {
+        let p = |s: &str| FinancialPeriod::from_str(s).unwrap();
+        assert_eq!(format!("{:#}", p("H1 2024")), "2024-H1");
+        assert_eq!(format!("{:#}", p("Q3 2024")), "2024-Q3");
+        assert_eq!(format!("{:#}", p("2024")), "2024");
+        for s in &["2024-H1", "2024-Q3", "2024"] {
+            assert_eq!(format!("{:#}", p(s)), *s);
+        }
+    }
+
     #[test]
     fn test_from_string() {
         assert_eq!(
@@ -279,4 +543,292 @@ mod tests {
             ParseError::InvalidPeriodValue
         );
     }
+
+    #[test]
+    fn test_from_iso_like_string() {
+        assert_eq!(
+            FinancialPeriod::from_str("2024-H1").unwrap(),
+            FinancialPeriod::Half {
+                half: 1,
+                year: 2024
+            }
+        );
+        assert_eq!(
+            FinancialPeriod::from_str("2024-Q3").unwrap(),
+            FinancialPeriod::Quarter {
+                quarter: 3,
+                year: 2024
+            }
+        );
+        assert_eq!(
+            FinancialPeriod::from_str("2024-H3").err().unwrap(),
+            ParseError::InvalidPeriodValue
+        );
+        assert_eq!(
+            FinancialPeriod::from_str("2024-").err().unwrap(),
+            ParseError::InvalidPeriodString
+        );
+    }
+
+    #[test]
+    fn test_period_dates() {
+        let date = |m, d| Date::from_ymd_opt(2024, m, d).unwrap();
+        let q3 = FinancialPeriod::Quarter {
+            quarter: 3,
+            year: 2024,
+        };
+        assert_eq!(q3.start_date(), date(7, 1));
+        assert_eq!(q3.end_date(), date(9, 30));
+
+        let h1 = FinancialPeriod::Half {
+            half: 1,
+            year: 2024,
+        };
+        assert_eq!(h1.start_date(), date(1, 1));
+        assert_eq!(h1.end_date(), date(6, 30));
+        assert!(h1.contains(date(6, 30)));
+        assert!(!h1.contains(date(7, 1)));
+
+        let h2 = FinancialPeriod::Half {
+            half: 2,
+            year: 2024,
+        };
+        assert_eq!(h2.end_date(), date(12, 31));
+
+        let year = FinancialPeriod::Year { year: 2024 };
+        assert_eq!(year.start_date(), date(1, 1));
+        assert_eq!(year.end_date(), date(12, 31));
+
+        let last = FinancialPeriod::Quarter {
+            quarter: 4,
+            year: 9999,
+        };
+        assert_eq!(last.end_date(), Date::from_ymd_opt(9999, 12, 31).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_period_dates() {
+        let date = Date::from_ymd_opt(2019, 12, 1).unwrap();
+        let zero = FinancialPeriod::Quarter {
+            quarter: 0,
+            year: 2020,
+        };
+        assert!(!zero.contains(date));
+        assert!(!zero.contains_in(date, &FiscalCalendar::default()));
+        assert!(!FinancialPeriod::Half {
+            half: 3,
+            year: 2020
+        }
+        .contains(date));
+        assert!(!FinancialPeriod::Year { year: 1492 }.contains(date));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid financial period")]
+    fn test_invalid_period_start_date() {
+        FinancialPeriod::Half {
+            half: 0,
+            year: 2020,
+        }
+        .start_date();
+    }
+
+    #[test]
+    fn test_next_and_previous() {
+        let h1 = FinancialPeriod::Half {
+            half: 1,
+            year: 2024,
+        };
+        let h2 = FinancialPeriod::Half {
+            half: 2,
+            year: 2024,
+        };
+        assert_eq!(h1.next(), Some(h2.clone()));
+        assert_eq!(
+            h2.next(),
+            Some(FinancialPeriod::Half {
+                half: 1,
+                year: 2025
+            })
+        );
+        assert_eq!(h2.previous(), Some(h1.clone()));
+        assert_eq!(
+            h1.previous(),
+            Some(FinancialPeriod::Half {
+                half: 2,
+                year: 2023
+            })
+        );
+
+        let q4 = FinancialPeriod::Quarter {
+            quarter: 4,
+            year: 2024,
+        };
+        assert_eq!(
+            q4.next(),
+            Some(FinancialPeriod::Quarter {
+                quarter: 1,
+                year: 2025
+            })
+        );
+        assert_eq!(q4.next().and_then(|p| p.previous()), Some(q4));
+        assert_eq!(
+            FinancialPeriod::Year { year: 2024 }.previous(),
+            Some(FinancialPeriod::Year { year: 2023 })
+        );
+    }
+
+    #[test]
+    fn test_next_and_previous_invalid() {
+        let zero = FinancialPeriod::Quarter {
+            quarter: 0,
+            year: 2020,
+        };
+        assert_eq!(zero.next(), None);
+        assert_eq!(zero.previous(), None);
+        let five = FinancialPeriod::Quarter {
+            quarter: 5,
+            year: 2020,
+        };
+        assert_eq!(five.next(), None);
+        assert_eq!(
+            FinancialPeriod::Half {
+                half: 0,
+                year: 2020
+            }
+            .previous(),
+            None
+        );
+        assert_eq!(FinancialPeriod::Year { year: u16::MAX }.next(), None);
+
+        assert_eq!(
+            FinancialPeriod::Quarter {
+                quarter: 4,
+                year: 9999
+            }
+            .next(),
+            None
+        );
+        assert_eq!(FinancialPeriod::Year { year: 1900 }.previous(), None);
+        assert_eq!(
+            FinancialPeriod::Half {
+                half: 1,
+                year: 1900
+            }
+            .previous(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_fiscal_period_dates() {
+        let date = |y, m, d| Date::from_ymd_opt(y, m, d).unwrap();
+        let october = FiscalCalendar { start_month: 10 };
+        let q1 = FinancialPeriod::Quarter {
+            quarter: 1,
+            year: 2020,
+        };
+        assert_eq!(q1.start_date_in(&october), date(2019, 10, 1));
+        assert_eq!(q1.end_date_in(&october), date(2019, 12, 31));
+        let q4 = FinancialPeriod::Quarter {
+            quarter: 4,
+            year: 2020,
+        };
+        assert_eq!(q4.end_date_in(&october), date(2020, 9, 30));
+        let year = FinancialPeriod::Year { year: 2020 };
+        assert_eq!(year.start_date_in(&october), date(2019, 10, 1));
+        assert_eq!(year.end_date_in(&october), date(2020, 9, 30));
+        assert!(year.contains_in(date(2019, 11, 1), &october));
+        assert!(!year.contains_in(date(2020, 11, 1), &october));
+
+        let calendar = FiscalCalendar::default();
+        assert_eq!(q1.start_date_in(&calendar), q1.start_date());
+        assert_eq!(q4.end_date_in(&calendar), q4.end_date());
+    }
+
+    #[test]
+    fn test_calendar_from_period_end() {
+        let date = |y, m, d| Date::from_ymd_opt(y, m, d).unwrap();
+        let p = |s: &str| FinancialPeriod::from_str(s).unwrap();
+        let october = Some(FiscalCalendar { start_month: 10 });
+        assert_eq!(
+            FiscalCalendar::from_period_end(&p("Q1 2020"), date(2019, 12, 31)),
+            october
+        );
+        assert_eq!(
+            FiscalCalendar::from_period_end(&p("H2 2020"), date(2020, 9, 26)),
+            october
+        );
+        assert_eq!(
+            FiscalCalendar::from_period_end(&p("2020"), date(2020, 9, 30)),
+            october
+        );
+        assert_eq!(
+            FiscalCalendar::from_period_end(&p("Q2 2019"), date(2019, 6, 30)),
+            Some(FiscalCalendar::default())
+        );
+        assert_eq!(
+            FiscalCalendar::from_period_end(&p("Q1 2020"), date(2021, 3, 31)),
+            None
+        );
+        let invalid = FinancialPeriod::Quarter {
+            quarter: 5,
+            year: 2020,
+        };
+        assert_eq!(
+            FiscalCalendar::from_period_end(&invalid, date(2020, 3, 31)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        let p = |s: &str| FinancialPeriod::from_str(s).unwrap();
+        let mut periods = vec![
+            p("2019"),
+            p("H2 2019"),
+            p("Q4 2019"),
+            p("Q1 2020"),
+            p("Q3 2019"),
+            p("H1 2019"),
+            p("Q2 2019"),
+            p("Q1 2019"),
+            p("Q4 2018"),
+        ];
+        periods.sort();
+        let sorted: Vec<String> = periods.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            sorted,
+            vec![
+                "Q4 2018", "Q1 2019", "Q2 2019", "H1 2019", "Q3 2019", "Q4 2019", "H2 2019",
+                "2019", "Q1 2020"
+            ]
+        );
+        assert!(p("2018") < p("Q1 2019"));
+
+        let zero = FinancialPeriod::Quarter {
+            quarter: 0,
+            year: 2020,
+        };
+        assert_ne!(zero.cmp(&p("Q4 2019")), std::cmp::Ordering::Equal);
+        assert!(zero > p("Q4 2019"));
+        assert!(
+            FinancialPeriod::Half {
+                half: 0,
+                year: 2020
+            } > p("H2 2019")
+        );
+    }
+
+    #[test]
+    fn test_distance() {
+        let p = |s: &str| FinancialPeriod::from_str(s).unwrap();
+        assert_eq!(p("Q1 2019").distance(&p("Q3 2020")), 6);
+        assert_eq!(p("Q3 2020").distance(&p("Q1 2019")), -6);
+        assert_eq!(p("H2 2019").distance(&p("H1 2021")), 3);
+        assert_eq!(p("2019").distance(&p("2019")), 0);
+        assert_eq!(p("Q4 2019").distance(&p("2020")), 4);
+        assert_eq!(p("2019").distance(&p("Q1 2020")), 0);
+        assert_eq!(p("2019").distance(&p("Q4 2020")), 1);
+    }
 }
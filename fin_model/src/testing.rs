@@ -0,0 +1,181 @@
+/*!
+Provides deterministic, synthetic, data for tests and demonstrations.
+
+This module requires the `testing` feature. Values are generated from a seed
+using a small, self-contained, pseudo-random number generator so that the same
+seed always produces identical data, on any platform and with any version of
+its dependencies.
+
+## Example
+
+```rust,ignore
+use fin_model::testing::synthetic_analyst_data;
+
+let data = synthetic_analyst_data("AAPL".to_string(), 42);
+assert!(data.target.unwrap().data.validate().is_ok());
+```
+*/
+
+use std::collections::HashMap;
+
+use steel_cent::currency::USD;
+
+use crate::analysis::*;
+use crate::prelude::*;
+use crate::reporting::{FinancialPeriod, FiscalCalendar};
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Generate plausible analyst data for `symbol`; a price target, three months
+/// of consensus ratings, and consensus EPS for the four quarters of a year, all
+/// in US dollars. The symbol is combined with `seed`, so that a universe of
+/// symbols generated with one seed do not share identical data. The generated
+/// `PriceTarget` and `EPSConsensus` values always pass `validate`.
+pub fn synthetic_analyst_data(symbol: Symbol, seed: u64) -> AnalystData {
+    let mut rng = SplitMix64::new(seed ^ symbol_hash(&symbol));
+
+    let year = 2015 + rng.below(10) as u16;
+    let as_of = Date::from_ymd_opt(i32::from(year), 12, 1).unwrap();
+
+    let price = 20.0 + rng.unit() * 480.0;
+    let average = price * (0.9 + rng.unit() * 0.4);
+    let target = PriceTarget {
+        high: PerShare(dollars(average * (1.05 + rng.unit() * 0.35))),
+        low: PerShare(dollars(average * (0.7 + rng.unit() * 0.25))),
+        average: PerShare(dollars(average)),
+        number_of_analysts: 1 + rng.below(40) as Counter,
+    };
+
+    let ratings = (9..=11)
+        .map(|month| {
+            let mut ratings = Ratings {
+                ratings: HashMap::new(),
+                scale_mark: None,
+            };
+            for code in 1..=5 {
+                let count = rng.below(12) as Counter;
+                if count > 0 {
+                    let rating = RatingType::from_code(code, CodeScale::OneIsBuy).unwrap();
+                    ratings.ratings.insert(rating, count);
+                }
+            }
+            if ratings.is_empty() {
+                ratings.ratings.insert(RatingType::Hold, 1);
+            }
+            ratings.recompute_scale_mark();
+            let start = Date::from_ymd_opt(i32::from(year), month, 1).unwrap();
+            let end = Date::from_ymd_opt(i32::from(year), month + 1, 1)
+                .unwrap()
+                .pred_opt()
+                .unwrap();
+            Bounded::new(
+                start.and_hms_opt(0, 0, 0).unwrap(),
+                end.and_hms_opt(0, 0, 0).unwrap(),
+                ratings,
+            )
+            .with_source("synthetic")
+        })
+        .collect();
+
+    let annual_eps = price / (10.0 + rng.unit() * 30.0);
+    let eps = (1..=4)
+        .map(|quarter| {
+            let fiscal_period = FinancialPeriod::Quarter { quarter, year };
+            let consensus = annual_eps / 4.0 * (0.8 + rng.unit() * 0.4);
+            let spread = consensus * (0.05 + rng.unit() * 0.25);
+            let fiscal_end_date = fiscal_period.end_date();
+            EPSConsensus {
+                consensus: PerShare(dollars(consensus)),
+                high_estimate: Some(PerShare(dollars(consensus + spread))),
+                low_estimate: Some(PerShare(dollars(consensus - spread))),
+                number_of_estimates: 1 + rng.below(30) as Counter,
+                fiscal_period: fiscal_period.clone(),
+                fiscal_calendar: FiscalCalendar::default(),
+                fiscal_end_date,
+                next_report_date: fiscal_end_date
+                    + chrono::Duration::days(20 + rng.below(25) as i64),
+                published: fiscal_period.start_date(),
+                attribution: None,
+            }
+        })
+        .collect();
+
+    AnalystData {
+        target: Some(
+            Snapshot::new(as_of.and_hms_opt(0, 0, 0).unwrap(), target).with_source("synthetic"),
+        ),
+        ratings,
+        eps,
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        SplitMix64(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+fn symbol_hash(symbol: &str) -> u64 {
+    symbol.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn dollars(value: f64) -> Money {
+    crate::money::money_from_f64(USD, value)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_data_is_deterministic() {
+        let data = synthetic_analyst_data("AAPL".to_string(), 42);
+        assert_eq!(data, synthetic_analyst_data("AAPL".to_string(), 42));
+        assert_ne!(data, synthetic_analyst_data("AAPL".to_string(), 43));
+        assert_ne!(data, synthetic_analyst_data("MSFT".to_string(), 42));
+    }
+
+    #[test]
+    fn test_synthetic_data_is_valid() {
+        for seed in 0..200 {
+            let data = synthetic_analyst_data("AAPL".to_string(), seed);
+            assert_eq!(data.target.unwrap().data.validate(), Ok(()));
+            assert_eq!(data.ratings.len(), 3);
+            assert!(data.ratings.iter().all(|r| !r.data.is_empty()));
+            assert_eq!(data.eps.len(), 4);
+            for eps in data.eps {
+                assert_eq!(eps.validate(), Ok(()));
+            }
+        }
+    }
+}